@@ -0,0 +1,194 @@
+use crate::Color;
+use crate::conversion::ColorConversion;
+use crate::palette::display_rgb;
+use crate::spaces::{from_linear, to_linear};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VisionDeficiency {
+    // No long-wavelength (red) cones
+    Protanopia,
+    // No medium-wavelength (green) cones
+    Deuteranopia,
+    // No short-wavelength (blue) cones
+    Tritanopia,
+}
+
+impl VisionDeficiency {
+    // Machado, Oliveira and Fernandes (2009) at full severity, applied to linear RGB.
+    fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            VisionDeficiency::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            VisionDeficiency::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            VisionDeficiency::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+}
+
+fn linear_rgb(color: &Color) -> Option<[f32; 3]> {
+    let (r, g, b) = display_rgb(color)?;
+
+    Some([
+        to_linear(r as f32 / 255.0),
+        to_linear(g as f32 / 255.0),
+        to_linear(b as f32 / 255.0),
+    ])
+}
+
+impl Color {
+    // WCAG relative luminance, 0 for black to 1 for white.
+    pub fn luminance(&self) -> Option<f32> {
+        let [r, g, b] = linear_rgb(self)?;
+
+        Some(0.2126 * r + 0.7152 * g + 0.0722 * b)
+    }
+
+    // WCAG contrast ratio, from 1 for identical colors to 21 for black on white.
+    pub fn contrast_ratio(&self, other: Color) -> Option<f32> {
+        let a = self.luminance()?;
+        let b = other.luminance()?;
+
+        Some((a.max(b) + 0.05) / (a.min(b) + 0.05))
+    }
+
+    // Whether white text reads better on it than black, the usual test for a dark background.
+    pub fn is_dark(&self) -> Option<bool> {
+        let luminance = self.luminance()?;
+
+        Some((1.05 / (luminance + 0.05)) > (luminance + 0.05) / 0.05)
+    }
+
+    // How the color looks with the given color vision deficiency.
+    pub fn simulate(&self, deficiency: VisionDeficiency) -> Color {
+        let Some(rgb) = linear_rgb(self) else {
+            return *self;
+        };
+
+        let channel = |row: [f32; 3]| {
+            let linear = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2];
+            (from_linear(linear.clamp(0.0, 1.0)) * 255.0).round() as u8
+        };
+
+        let [r, g, b] = deficiency.matrix().map(channel);
+
+        match self.alpha() {
+            255 => Color::Rgb(r, g, b),
+            alpha => Color::Rgba(r, g, b, alpha),
+        }
+    }
+}
+
+// Black or white, whichever reads better on `background`.
+pub fn best_text_color_for(background: Color) -> Color {
+    let black = Color::Black.contrast_ratio(background).unwrap_or(0.0);
+    let white = Color::White.contrast_ratio(background).unwrap_or(0.0);
+
+    if black >= white {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
+
+// Moves the lightness of `foreground` as little as possible, keeping its hue,
+// until it reaches `min_ratio` against `background` (4.5 is WCAG AA for body text).
+// Falls back to black or white when no lightness gets there.
+pub fn ensure_contrast(foreground: Color, background: Color, min_ratio: f32) -> Color {
+    let ratio = |color: Color| color.contrast_ratio(background).unwrap_or(0.0);
+
+    if ratio(foreground) >= min_ratio {
+        return foreground;
+    }
+
+    let Some(start) = foreground.to_oklch() else {
+        return best_text_color_for(background);
+    };
+
+    let with_lightness = |l: f32| Color::oklch(l, start.c, start.h);
+
+    // Binary search between the current lightness and one extreme for the closest that passes
+    let search = |target: f32| -> Option<(f32, Color)> {
+        if ratio(with_lightness(target)) < min_ratio {
+            return None;
+        }
+
+        let (mut near, mut far) = (start.l, target);
+
+        for _ in 0..24 {
+            let middle = (near + far) / 2.0;
+
+            if ratio(with_lightness(middle)) >= min_ratio {
+                far = middle;
+            } else {
+                near = middle;
+            }
+        }
+
+        Some(((far - start.l).abs(), with_lightness(far)))
+    };
+
+    match (search(1.0), search(0.0)) {
+        (Some(lighter), Some(darker)) => if lighter.0 <= darker.0 { lighter.1 } else { darker.1 },
+        (Some((_, color)), None) | (None, Some((_, color))) => color,
+        (None, None) => best_text_color_for(background),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Color = Color::Rgb(0, 0, 0);
+    const WHITE: Color = Color::Rgb(255, 255, 255);
+
+    #[test]
+    fn contrast_ratio_spans_one_to_twenty_one() {
+        let ratio = BLACK.contrast_ratio(WHITE).unwrap();
+
+        assert!((ratio - 21.0).abs() < 0.001, "{}", ratio);
+        assert_eq!(WHITE.contrast_ratio(BLACK), BLACK.contrast_ratio(WHITE));
+        assert_eq!(Color::Rgb(90, 90, 90).contrast_ratio(Color::Rgb(90, 90, 90)), Some(1.0));
+        assert!((Color::Rgb(118, 118, 118).contrast_ratio(WHITE).unwrap() - 4.54).abs() < 0.01);
+        assert_eq!(Color::Transparent.contrast_ratio(WHITE), None);
+    }
+
+    #[test]
+    fn ensure_contrast_reaches_the_target_ratio() {
+        let cases = [
+            (Color::Rgb(120, 120, 120), Color::Rgb(100, 100, 100), 4.5),
+            (Color::Rgb(255, 136, 0), WHITE, 4.5),
+            (Color::Rgb(30, 60, 200), BLACK, 7.0),
+            (Color::Rgb(200, 40, 40), Color::Rgb(120, 0, 0), 3.0),
+        ];
+
+        for (foreground, background, target) in cases {
+            let adjusted = ensure_contrast(foreground, background, target);
+            let ratio = adjusted.contrast_ratio(background).unwrap();
+
+            assert!(ratio >= target, "{} on {} gave {} at {}", foreground, background, adjusted, ratio);
+        }
+    }
+
+    #[test]
+    fn ensure_contrast_keeps_colors_that_already_pass() {
+        assert_eq!(ensure_contrast(BLACK, WHITE, 4.5), BLACK);
+        assert_eq!(ensure_contrast(Color::Rgb(0, 0, 200), WHITE, 4.5), Color::Rgb(0, 0, 200));
+    }
+
+    #[test]
+    fn best_text_color_picks_the_readable_one() {
+        assert_eq!(best_text_color_for(WHITE), Color::Black);
+        assert_eq!(best_text_color_for(Color::Rgb(20, 20, 60)), Color::White);
+    }
+}
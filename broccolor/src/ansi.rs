@@ -0,0 +1,356 @@
+use crate::{Attributes, Color, Style, StyledText};
+use crate::gradient::{graphemes, is_extending};
+use crate::palette::BASIC;
+
+pub(crate) enum Token<'a> {
+    Escape(&'a str),
+    Text(&'a str),
+}
+
+// Splits text into escape sequences and the plain text between them. CSI sequences end
+// at their final byte, OSC and the other string sequences at BEL or ST, and anything else
+// after one character. An unfinished sequence runs to the end of the text.
+pub(crate) fn tokens(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('\x1b') {
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+
+        let sequence = &rest[start..];
+        let bytes = sequence.as_bytes();
+
+        let end = match bytes.get(1) {
+            Some(b'[') => bytes[2..]
+                .iter()
+                .position(|b| (0x40..=0x7E).contains(b))
+                .map(|i| i + 3),
+            Some(b']' | b'P' | b'_' | b'^' | b'X') => {
+                let bel = sequence.find('\x07').map(|i| i + 1);
+                let st = sequence[2..].find("\x1b\\").map(|i| i + 4);
+
+                match (bel, st) {
+                    (Some(bel), Some(st)) => Some(bel.min(st)),
+                    (bel, st) => bel.or(st),
+                }
+            }
+            Some(_) => {
+                // Intermediate bytes, as in `ESC ( B`, then the final character
+                let intermediates = bytes[1..].iter().take_while(|b| (0x20..=0x2F).contains(*b)).count();
+
+                sequence[1 + intermediates..].chars().next().map(|c| 1 + intermediates + c.len_utf8())
+            }
+            None => None,
+        };
+
+        let end = end.unwrap_or(sequence.len());
+        tokens.push(Token::Escape(&sequence[..end]));
+        rest = &sequence[end..];
+    }
+
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+
+    tokens
+}
+
+// The parameters of an SGR sequence, or `None` for any other escape.
+fn sgr_params(escape: &str) -> Option<&str> {
+    let params = escape.strip_prefix("\x1b[")?.strip_suffix('m')?;
+
+    params
+        .chars()
+        .all(|c| c.is_ascii_digit() || c == ';' || c == ':')
+        .then_some(params)
+}
+
+// `5;n` or `2;r;g;b` after 38, 48 or 58, whether the parts are separate parameters or
+// colon sub-parameters. The colon form may carry a color space id before the channels.
+fn extended_color<'a>(sub: &[&'a str], params: &mut impl Iterator<Item = &'a str>) -> Option<Color> {
+    let number = |value: &str| value.parse::<u8>().ok();
+
+    if !sub.is_empty() {
+        return match sub[0] {
+            "5" => sub.get(1).and_then(|n| number(n)).map(Color::Indexed),
+            "2" if sub.len() >= 4 => {
+                let channels = &sub[sub.len() - 3..];
+                Some(Color::Rgb(number(channels[0])?, number(channels[1])?, number(channels[2])?))
+            }
+            _ => None,
+        };
+    }
+
+    match params.next()? {
+        "5" => number(params.next()?).map(Color::Indexed),
+        "2" => {
+            let (r, g, b) = (params.next()?, params.next()?, params.next()?);
+            Some(Color::Rgb(number(r)?, number(g)?, number(b)?))
+        }
+        _ => None,
+    }
+}
+
+// Applies the parameters of one SGR sequence to `style`.
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let mut params = params.split(';');
+
+    while let Some(param) = params.next() {
+        let mut parts = param.split(':');
+        let code = parts.next().unwrap_or_default();
+        let sub: Vec<&str> = parts.collect();
+
+        let code: u16 = match code {
+            "" => 0,
+            code => match code.parse() {
+                Ok(code) => code,
+                Err(_) => continue,
+            },
+        };
+
+        style = match code {
+            0 => Style::new(),
+            1 => style.attrs(Attributes::BOLD),
+            2 => style.attrs(Attributes::DIM),
+            3 => style.attrs(Attributes::ITALIC),
+            4 => match sub.first().copied() {
+                Some("0") => style.remove(Attributes::UNDERLINES),
+                Some("2") => style.attrs(Attributes::DOUBLE_UNDERLINE),
+                Some("3") => style.attrs(Attributes::CURLY_UNDERLINE),
+                Some("4") => style.attrs(Attributes::DOTTED_UNDERLINE),
+                Some("5") => style.attrs(Attributes::DASHED_UNDERLINE),
+                _ => style.attrs(Attributes::UNDERLINE),
+            },
+            5 => style.attrs(Attributes::BLINK),
+            6 => style.attrs(Attributes::RAPID_BLINK),
+            7 => style.attrs(Attributes::REVERSE),
+            8 => style.attrs(Attributes::HIDDEN),
+            9 => style.attrs(Attributes::STRIKETHROUGH),
+            21 => style.attrs(Attributes::DOUBLE_UNDERLINE),
+            22 => style.remove(Attributes::BOLD | Attributes::DIM),
+            23 => style.remove(Attributes::ITALIC),
+            24 => style.remove(Attributes::UNDERLINES),
+            25 => style.remove(Attributes::BLINK | Attributes::RAPID_BLINK),
+            27 => style.remove(Attributes::REVERSE),
+            28 => style.remove(Attributes::HIDDEN),
+            29 => style.remove(Attributes::STRIKETHROUGH),
+            53 => style.attrs(Attributes::OVERLINE),
+            55 => style.remove(Attributes::OVERLINE),
+            30..=37 => style.fg(BASIC[code as usize - 30]),
+            90..=97 => style.fg(BASIC[code as usize - 90 + 8]),
+            40..=47 => style.bg(BASIC[code as usize - 40]),
+            100..=107 => style.bg(BASIC[code as usize - 100 + 8]),
+            38 | 48 | 58 => match extended_color(&sub, &mut params) {
+                Some(color) if code == 38 => style.fg(color),
+                Some(color) if code == 48 => style.bg(color),
+                Some(color) => style.underline_color(color),
+                None => style,
+            },
+            39 => Style { fg: None, ..style },
+            49 => Style { bg: None, ..style },
+            59 => Style { underline_color: None, ..style },
+            _ => style,
+        };
+    }
+
+    style
+}
+
+// Splits text containing SGR sequences into runs with the style each one is shown in.
+// Other escape sequences, such as cursor movement or hyperlinks, are dropped.
+pub fn parse_ansi(text: &str) -> Vec<(Style, String)> {
+    let mut spans: Vec<(Style, String)> = Vec::new();
+    let mut style = Style::new();
+
+    for token in tokens(text) {
+        match token {
+            Token::Escape(escape) => {
+                if let Some(params) = sgr_params(escape) {
+                    style = apply_sgr(style, params);
+                }
+            }
+            Token::Text(text) => match spans.last_mut() {
+                Some((last, content)) if *last == style => content.push_str(text),
+                _ => spans.push((style, text.to_string())),
+            },
+        }
+    }
+
+    spans
+}
+
+// The reverse of `parse_ansi`: one SGR sequence per change of style, reset at the end.
+pub fn render_ansi(spans: &[(Style, String)]) -> String {
+    StyledText::from_spans(spans.to_vec()).render()
+}
+
+impl StyledText {
+    pub fn from_ansi(text: &str) -> Self {
+        Self::from_spans(parse_ansi(text))
+    }
+}
+
+// Removes every escape sequence, not only SGR.
+pub fn strip_ansi(text: &str) -> String {
+    tokens(text)
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text),
+            Token::Escape(_) => None,
+        })
+        .collect()
+}
+
+// Columns a character takes up in a terminal: 2 for East Asian wide and emoji,
+// 0 for controls and combining marks, 1 otherwise.
+fn char_width(c: char) -> usize {
+    let code = c as u32;
+
+    if code < 0x20 || (0x7F..0xA0).contains(&code) || is_extending(c) || matches!(code, 0x200B..=0x200F | 0x2060 | 0xFEFF) {
+        return 0;
+    }
+
+    let wide = matches!(
+        code,
+        0x1100..=0x115F
+            | 0x231A..=0x231B
+            | 0x2329..=0x232A
+            | 0x23E9..=0x23EC
+            | 0x23F0 | 0x23F3
+            | 0x25FD..=0x25FE
+            | 0x2614..=0x2615
+            | 0x2648..=0x2653
+            | 0x267F | 0x2693 | 0x26A1
+            | 0x26AA..=0x26AB
+            | 0x26BD..=0x26BE
+            | 0x26C4..=0x26C5
+            | 0x26CE | 0x26D4 | 0x26EA
+            | 0x26F2..=0x26F3
+            | 0x26F5 | 0x26FA | 0x26FD | 0x2705
+            | 0x270A..=0x270B
+            | 0x2728 | 0x274C | 0x274E
+            | 0x2753..=0x2755
+            | 0x2757
+            | 0x2795..=0x2797
+            | 0x27B0 | 0x27BF
+            | 0x2B1B..=0x2B1C
+            | 0x2B50 | 0x2B55
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xA960..=0xA97F
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE10..=0xFE19
+            | 0xFE30..=0xFE6F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x16FE0..=0x18AFF
+            | 0x1B000..=0x1B2FF
+            | 0x1F004 | 0x1F0CF | 0x1F18E
+            | 0x1F191..=0x1F19A
+            | 0x1F1E6..=0x1F1FF
+            | 0x1F200..=0x1F251
+            | 0x1F300..=0x1F64F
+            | 0x1F680..=0x1F6FF
+            | 0x1F7E0..=0x1F7EB
+            | 0x1F90C..=0x1F9FF
+            | 0x1FA70..=0x1FAFF
+            | 0x20000..=0x3FFFD
+    );
+
+    if wide {
+        2
+    } else {
+        1
+    }
+}
+
+fn grapheme_width(grapheme: &str) -> usize {
+    // A variation selector 16 asks for emoji presentation, which is wide
+    if grapheme.contains('\u{FE0F}') {
+        return 2;
+    }
+
+    grapheme.chars().map(char_width).max().unwrap_or(0).min(2)
+}
+
+// Columns the text takes up on one line once escape sequences are left out.
+pub fn visible_width(text: &str) -> usize {
+    graphemes(&strip_ansi(text)).into_iter().map(grapheme_width).sum()
+}
+
+// The graphemes that fit within columns `start..end`, keeping every escape sequence so the
+// styles in effect, and the resets that close them, still apply. Wide characters that
+// straddle either edge are left out.
+pub fn slice(text: &str, start: usize, end: usize) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut column = 0;
+
+    for token in tokens(text) {
+        match token {
+            Token::Escape(escape) => out.push_str(escape),
+            Token::Text(text) => {
+                for grapheme in graphemes(text) {
+                    let width = grapheme_width(grapheme);
+
+                    if column >= start && column + width <= end && (width > 0 || column < end) {
+                        out.push_str(grapheme);
+                    }
+
+                    column += width;
+                }
+            }
+        }
+    }
+
+    out
+}
+
+// At most `width` columns of the text, with its escape sequences intact.
+pub fn truncate(text: &str, width: usize) -> String {
+    slice(text, 0, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_and_parse_round_trip() {
+        let spans = vec![
+            (Style::new().fg(Color::Red).bold(), "error".to_string()),
+            (Style::new(), ": ".to_string()),
+            (Style::new().fg(Color::Rgb(1, 2, 3)).bg(Color::Indexed(236)).underline(), "detail".to_string()),
+        ];
+
+        assert_eq!(parse_ansi(&render_ansi(&spans)), spans);
+    }
+
+    #[test]
+    fn parse_reads_combined_and_colon_parameters() {
+        let spans = parse_ansi("\x1b[1;38:2::255:0:0mhot\x1b[22;39m cold\x1b]8;;https://example.com\x07!\x1b[m");
+
+        assert_eq!(spans[0], (Style::new().fg(Color::Rgb(255, 0, 0)).bold(), "hot".to_string()));
+        assert_eq!(spans[1], (Style::new(), " cold!".to_string()));
+    }
+
+    #[test]
+    fn widths_skip_escapes_and_count_wide_graphemes() {
+        assert_eq!(visible_width("\x1b[31mabc\x1b[0m"), 3);
+        assert_eq!(visible_width("日本"), 4);
+        assert_eq!(visible_width("e\u{301}👍🏽❤\u{fe0f}"), 5);
+    }
+
+    #[test]
+    fn slices_keep_escapes_and_drop_split_wide_characters() {
+        assert_eq!(slice("\x1b[31mabcdef\x1b[0m", 1, 3), "\x1b[31mbc\x1b[0m");
+        assert_eq!(slice("日本語", 1, 5), "本");
+        assert_eq!(truncate("日本語", 3), "日");
+        assert_eq!(truncate("abc", 10), "abc");
+    }
+}
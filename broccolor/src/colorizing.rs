@@ -1,5 +1,4 @@
-use crate::Color;
-use crate::conversion::ColorConversion;
+use crate::{Color, ColorLevel, ColorSpace, Gradient, Stream, Style, StyledText};
 
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 
@@ -14,6 +13,17 @@ impl<T: Display> Display for ColoredText<T> {
     }
 }
 
+impl<T: Display> From<ColoredText<T>> for StyledText {
+    fn from(text: ColoredText<T>) -> Self {
+        let style = match text.color {
+            Color::Transparent => Style::new(),
+            color => Style::new().fg(color),
+        };
+
+        StyledText::new().styled(style, &text.content.to_string())
+    }
+}
+
 impl<T: Debug> Debug for ColoredText<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         write!(f, "{}", format!("{:?}", self.content).term_colorize(self.color))
@@ -22,37 +32,107 @@ impl<T: Debug> Debug for ColoredText<T> {
 
 impl<T: Display + Debug> TextStyle for T {
     fn colorize(&self, color: Color) -> String {
-        format!("{}{}{}", color.to_ansi_code(), self, Color::reset())
+        if color == Color::Transparent {
+            return self.to_string();
+        }
+
+        Style::new().fg(color).paint(self)
     }
 
     fn term_colorize(&self, color: Color) -> String {
-        if !cfg!(target_arch = "wasm32") {
-            if color_support() {
-                return format!("{}{}{}", color.to_ansi_code(), self, Color::reset())
-            }
+        if color == Color::Transparent {
+            return self.to_string();
         }
 
-        self.to_string()
+        Style::new().fg(color).paint_for(self, term_level())
     }
 
     fn background(&self, color: Color) -> String {
-        format!("{}{}{}", color.to_background_ansi_code(), self, Color::reset())
+        if color == Color::Transparent {
+            return self.to_string();
+        }
+
+        Style::new().bg(color).paint(self)
     }
 
     fn bold(&self) -> String {
-        format!("{}{}{}", Color::bold(), self, Color::reset())
+        Style::new().bold().paint(self)
+    }
+
+    fn dim(&self) -> String {
+        Style::new().dim().paint(self)
     }
 
     fn italic(&self) -> String {
-        format!("{}{}{}", Color::italic(), self, Color::reset())
+        Style::new().italic().paint(self)
     }
 
     fn underline(&self) -> String {
-        format!("{}{}{}", Color::underline(), self, Color::reset())
+        Style::new().underline().paint(self)
+    }
+
+    fn double_underline(&self) -> String {
+        Style::new().double_underline().paint(self)
+    }
+
+    fn curly_underline(&self) -> String {
+        Style::new().curly_underline().paint(self)
+    }
+
+    fn dotted_underline(&self) -> String {
+        Style::new().dotted_underline().paint(self)
+    }
+
+    fn dashed_underline(&self) -> String {
+        Style::new().dashed_underline().paint(self)
+    }
+
+    fn underline_color(&self, color: Color) -> String {
+        Style::new().underline_color(color).paint(self)
+    }
+
+    fn blink(&self) -> String {
+        Style::new().blink().paint(self)
+    }
+
+    fn rapid_blink(&self) -> String {
+        Style::new().rapid_blink().paint(self)
+    }
+
+    fn reverse(&self) -> String {
+        Style::new().reverse().paint(self)
+    }
+
+    fn hidden(&self) -> String {
+        Style::new().hidden().paint(self)
     }
 
     fn strikethrough(&self) -> String {
-        format!("{}{}{}", Color::strikethrough(), self, Color::reset())
+        Style::new().strikethrough().paint(self)
+    }
+
+    fn overline(&self) -> String {
+        Style::new().overline().paint(self)
+    }
+
+    fn style(&self, style: Style) -> String {
+        style.paint(self)
+    }
+
+    fn gradient(&self, stops: &[Color], space: ColorSpace) -> String {
+        Gradient::new(stops, space).paint_for(&self.to_string(), term_level())
+    }
+
+    fn vertical_gradient(&self, stops: &[Color], space: ColorSpace) -> String {
+        Gradient::new(stops, space).vertical().paint_for(&self.to_string(), term_level())
+    }
+
+    fn rainbow(&self) -> String {
+        Gradient::rainbow().paint_for(&self.to_string(), term_level())
+    }
+
+    fn term_style(&self, style: Style) -> String {
+        style.paint_for(self, term_level())
     }
 }
 
@@ -62,23 +142,32 @@ pub trait TextStyle {
     fn term_colorize(&self, color: Color) -> String;
     fn background(&self, color: Color) -> String;
     fn bold(&self) -> String;
+    fn dim(&self) -> String;
     fn italic(&self) -> String;
     fn underline(&self) -> String;
+    fn double_underline(&self) -> String;
+    fn curly_underline(&self) -> String;
+    fn dotted_underline(&self) -> String;
+    fn dashed_underline(&self) -> String;
+    fn underline_color(&self, color: Color) -> String;
+    fn blink(&self) -> String;
+    fn rapid_blink(&self) -> String;
+    fn reverse(&self) -> String;
+    fn hidden(&self) -> String;
     fn strikethrough(&self) -> String;
+    fn overline(&self) -> String;
+    fn style(&self, style: Style) -> String;
+    fn term_style(&self, style: Style) -> String;
+    fn gradient(&self, stops: &[Color], space: ColorSpace) -> String;
+    fn vertical_gradient(&self, stops: &[Color], space: ColorSpace) -> String;
+    fn rainbow(&self) -> String;
 }
 
-fn color_support() -> bool {
-    if let Ok(term) = std::env::var("TERM") {
-        if term.contains("xterm") || term.contains("screen") || term.contains("vt100") {
-            true
-        } else {
-            false
-        }
+// What stdout can show; wasm output never goes to a terminal.
+fn term_level() -> ColorLevel {
+    if cfg!(target_arch = "wasm32") {
+        ColorLevel::None
     } else {
-        if let Ok(_) = std::env::var("COLORTERM") {
-            true
-        } else {
-            false
-        }
+        ColorLevel::detect(Stream::Stdout)
     }
 }
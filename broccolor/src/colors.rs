@@ -1,5 +1,5 @@
 #[allow(dead_code)]
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Color {
     // Primary Colors
     Red,
@@ -8,6 +8,7 @@ pub enum Color {
     Blue,
     Magenta,
     Cyan,
+    #[default]
     White,
     Black,
 
@@ -42,21 +43,14 @@ pub enum Color {
     SlateGray,
     Charcoal,
 
-    // RGB, RGBA, Hex, Indexed and Transparent
+    // RGB, RGBA, Indexed and Transparent; hex codes are parsed into RGB(A)
     Transparent,
     Rgb(u8, u8, u8),       // Custom RGB
     Rgba(u8, u8, u8, u8),  // Custom RGBA
-    Hex(&'static str),     // Hexadecimal Color Code
     Indexed(u8),           // ANSI Indexed (0–255)
     Gray(u8),              // Gray shades (0–23)
 }
 
-impl Default for Color {
-    fn default() -> Self {
-        Color::White
-    }
-}
-
 #[allow(dead_code)]
 impl Color {
     pub fn reset() -> &'static str {
@@ -79,7 +73,153 @@ impl Color {
         "\x1b[9m"
     }
 
+    pub fn dim() -> &'static str {
+        "\x1b[2m"
+    }
+
+    pub fn blink() -> &'static str {
+        "\x1b[5m"
+    }
+
+    pub fn rapid_blink() -> &'static str {
+        "\x1b[6m"
+    }
+
+    pub fn reverse() -> &'static str {
+        "\x1b[7m"
+    }
+
+    pub fn hidden() -> &'static str {
+        "\x1b[8m"
+    }
+
+    pub fn overline() -> &'static str {
+        "\x1b[53m"
+    }
+
+    pub fn double_underline() -> &'static str {
+        "\x1b[21m"
+    }
+
+    // Styled underlines use the `4:x` sub-parameter form, understood by kitty, WezTerm, VTE and others.
+    pub fn curly_underline() -> &'static str {
+        "\x1b[4:3m"
+    }
+
+    pub fn dotted_underline() -> &'static str {
+        "\x1b[4:4m"
+    }
+
+    pub fn dashed_underline() -> &'static str {
+        "\x1b[4:5m"
+    }
+
     pub fn reset_style() -> &'static str {
         "\x1b[22m"
     }
-}
\ No newline at end of file
+
+    // Bold and dim share a reset.
+    pub fn reset_intensity() -> &'static str {
+        "\x1b[22m"
+    }
+
+    pub fn reset_italic() -> &'static str {
+        "\x1b[23m"
+    }
+
+    // Clears every underline style, double and curly included.
+    pub fn reset_underline() -> &'static str {
+        "\x1b[24m"
+    }
+
+    pub fn reset_blink() -> &'static str {
+        "\x1b[25m"
+    }
+
+    pub fn reset_reverse() -> &'static str {
+        "\x1b[27m"
+    }
+
+    pub fn reset_hidden() -> &'static str {
+        "\x1b[28m"
+    }
+
+    pub fn reset_strikethrough() -> &'static str {
+        "\x1b[29m"
+    }
+
+    pub fn reset_overline() -> &'static str {
+        "\x1b[55m"
+    }
+
+    pub fn reset_foreground() -> &'static str {
+        "\x1b[39m"
+    }
+
+    pub fn reset_background() -> &'static str {
+        "\x1b[49m"
+    }
+
+    pub fn reset_underline_color() -> &'static str {
+        "\x1b[59m"
+    }
+
+    // Parses `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` (the `#` is optional) in const contexts,
+    // giving `Rgb`, or `Rgba` when the alpha is below `ff`. `None` for anything else.
+    pub const fn try_from_hex(hex: &str) -> Option<Color> {
+        let bytes = hex.as_bytes();
+        let digits = match bytes {
+            [b'#', rest @ ..] => rest,
+            rest => rest,
+        };
+
+        let short = digits.len() == 3 || digits.len() == 4;
+
+        if !short && digits.len() != 6 && digits.len() != 8 {
+            return None;
+        }
+
+        let mut channels = [255u8; 4];
+        let count = if short { digits.len() } else { digits.len() / 2 };
+        let mut i = 0;
+
+        while i < count {
+            let value = if short {
+                match hex_digit(digits[i]) {
+                    Some(d) => d * 17,
+                    None => return None,
+                }
+            } else {
+                match (hex_digit(digits[i * 2]), hex_digit(digits[i * 2 + 1])) {
+                    (Some(high), Some(low)) => high * 16 + low,
+                    _ => return None,
+                }
+            };
+
+            channels[i] = value;
+            i += 1;
+        }
+
+        let [r, g, b, a] = channels;
+
+        Some(if a == 255 { Color::Rgb(r, g, b) } else { Color::Rgba(r, g, b, a) })
+    }
+
+    // `try_from_hex` for constants, e.g. `const ACCENT: Color = Color::from_hex_const("#ff8800");`.
+    // Invalid input fails the build when evaluated in a const, and panics otherwise.
+    pub const fn from_hex_const(hex: &str) -> Color {
+        match Color::try_from_hex(hex) {
+            Some(color) => color,
+            None => panic!("invalid hex color, expected #rgb, #rgba, #rrggbb or #rrggbbaa"),
+        }
+    }
+}
+
+const fn hex_digit(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
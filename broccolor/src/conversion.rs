@@ -1,5 +1,9 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::str::FromStr;
-use crate::Color;
+use crate::{Color, ColorLevel};
+use crate::css;
+use crate::spaces::{Hsl, Hsv, Lab, Lch, Oklab, Oklch};
+use crate::palette::{basic_index, display_rgb, nearest_ansi16, nearest_ansi256, BASIC};
 
 #[allow(dead_code)]
 pub trait ColorConversion {
@@ -11,6 +15,19 @@ pub trait ColorConversion {
     fn to_css(&self) -> Option<String>;
     fn to_ansi_code(&self) -> String;
     fn to_background_ansi_code(&self) -> String;
+    fn to_underline_ansi_code(&self) -> String;
+    fn to_ansi256(&self) -> Option<u8>;
+    fn to_ansi16(&self) -> Option<u8>;
+    fn downsample(&self, level: ColorLevel) -> Option<Color>;
+    fn to_ansi_code_for(&self, level: ColorLevel) -> String;
+    fn to_background_ansi_code_for(&self, level: ColorLevel) -> String;
+    fn to_underline_ansi_code_for(&self, level: ColorLevel) -> String;
+    fn to_hsl(&self) -> Option<Hsl>;
+    fn to_hsv(&self) -> Option<Hsv>;
+    fn to_lab(&self) -> Option<Lab>;
+    fn to_lch(&self) -> Option<Lch>;
+    fn to_oklab(&self) -> Option<Oklab>;
+    fn to_oklch(&self) -> Option<Oklch>;
     fn indexed_to_rgba(index: u8) -> (f32, f32, f32, f32);
     fn from_hex(hex: &str) -> Result<Color, String>;
 }
@@ -30,63 +47,10 @@ impl ColorConversion for Color {
                 b as f32 / 255.0,
                 a as f32 / 255.0,
             )),
-            Color::Hex(ref hex) => {
-                if hex.len() == 7 || hex.len() == 9 {
-                    let r = u8::from_str_radix(&hex[1..3], 16).ok()?;
-                    let g = u8::from_str_radix(&hex[3..5], 16).ok()?;
-                    let b = u8::from_str_radix(&hex[5..7], 16).ok()?;
-                    let a = if hex.len() == 9 {
-                        u8::from_str_radix(&hex[7..9], 16).ok()?
-                    } else {
-                        255
-                    };
-                    Some((
-                        r as f32 / 255.0,
-                        g as f32 / 255.0,
-                        b as f32 / 255.0,
-                        a as f32 / 255.0,
-                    ))
-                } else {
-                    None
-                }
-            }
-            Color::Gray(g) => Some((
-                g as f32 / 255.0,
-                g as f32 / 255.0,
-                g as f32 / 255.0,
-                1.0,
-            )),
-            Color::Indexed(n) => {
-                match n {
-                    0..=7 => match n {
-                        0 => Some((0.0, 0.0, 0.0, 1.0)),
-                        1 => Some((1.0, 0.0, 0.0, 1.0)),
-                        2 => Some((0.0, 1.0, 0.0, 1.0)),
-                        3 => Some((1.0, 1.0, 0.0, 1.0)),
-                        4 => Some((0.0, 0.0, 1.0, 1.0)),
-                        5 => Some((1.0, 0.0, 1.0, 1.0)),
-                        6 => Some((0.0, 1.0, 1.0, 1.0)),
-                        7 => Some((1.0, 1.0, 1.0, 1.0)),
-                        _ => None
-                    },
-                    232..=255 => {
-                        let intensity = (n - 232) as f32 / 23.0;
-                        Some((intensity, intensity, intensity, 1.0))
-                    },
-                    _ => {
-                        Some((0.5, 0.5, 0.5, 1.0))
-                    }
-                }
-            },
+            // Palette entries, as terminals show them
+            Color::Gray(g) => Some(Self::indexed_to_rgba(232 + g.min(23))),
+            Color::Indexed(n) => Some(Self::indexed_to_rgba(n)),
             Color::Transparent => Some((0.0, 0.0, 0.0, 0.0)),
-            Color::Red => Some((1.0, 0.0, 0.0, 1.0)),
-            Color::Green => Some((0.0, 1.0, 0.0, 1.0)),
-            Color::Blue => Some((0.0, 0.0, 1.0, 1.0)),
-            Color::Yellow => Some((1.0, 1.0, 0.0, 1.0)),
-            Color::Magenta => Some((1.0, 0.0, 1.0, 1.0)),
-            Color::Cyan => Some((0.0, 1.0, 1.0, 1.0)),
-            Color::White => Some((1.0, 1.0, 1.0, 1.0)),
-            Color::Black => Some((0.0, 0.0, 0.0, 1.0)),
             Color::Orange => Some((1.0, 0.647, 0.0, 1.0)),
             Color::Pink => Some((1.0, 0.75, 0.8, 1.0)),
             Color::Lime => Some((0.0, 1.0, 0.0, 1.0)),
@@ -97,14 +61,6 @@ impl ColorConversion for Color {
             Color::Mint => Some((0.68, 1.0, 0.65, 1.0)),
             Color::Coral => Some((1.0, 0.5, 0.31, 1.0)),
             Color::Charcoal => Some((0.2, 0.2, 0.2, 1.0)),
-            Color::BrightRed => Some((1.0, 0.4, 0.4, 1.0)),
-            Color::BrightGreen => Some((0.4, 1.0, 0.4, 1.0)),
-            Color::BrightYellow => Some((1.0, 1.0, 0.4, 1.0)),
-            Color::BrightBlue => Some((0.4, 0.4, 1.0, 1.0)),
-            Color::BrightMagenta => Some((1.0, 0.4, 1.0, 1.0)),
-            Color::BrightCyan => Some((0.4, 1.0, 1.0, 1.0)),
-            Color::BrightWhite => Some((1.0, 1.0, 1.0, 1.0)),
-            Color::BrightBlack => Some((0.2, 0.2, 0.2, 1.0)),
             Color::Crimson => Some((0.86, 0.08, 0.24, 1.0)),
             Color::Gold => Some((1.0, 0.84, 0.0, 1.0)),
             Color::Silver => Some((0.75, 0.75, 0.75, 1.0)),
@@ -112,13 +68,16 @@ impl ColorConversion for Color {
             Color::LightGray => Some((0.8, 0.8, 0.8, 1.0)),
             Color::DarkGray => Some((0.25, 0.25, 0.25, 1.0)),
             Color::SlateGray => Some((0.44, 0.5, 0.56, 1.0)),
+            // The 16 basic colors are palette entries 0–15 too
+            _ => Some(Self::indexed_to_rgba(basic_index(*self)?)),
         }
     }
 
     fn to_rgba_u8(&self) -> Option<(u8,u8,u8,u8)> {
         let (r,g,b,a) = self.to_rgba()?;
+        let channel = |c: f32| (c * 255.0).round() as u8;
 
-        Some(((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, (a * 255.0) as u8))
+        Some((channel(r), channel(g), channel(b), channel(a)))
     }
 
     fn to_rgba_array(&self) -> Option<[f32;4]> {
@@ -144,6 +103,10 @@ impl ColorConversion for Color {
             _ => {
                 let (r, g, b, a) = self.to_rgba_u8()?;
 
+                if a == 255 {
+                    return Some(format!("rgb({}, {}, {})", r, g, b));
+                }
+
                 Some(format!("rgba({}, {}, {}, {})", r, g, b, a as f32 / 255.0))
             },
         }
@@ -198,14 +161,6 @@ impl ColorConversion for Color {
             }
 
             Color::Rgba(r, g, b, _) => format!("\x1b[38;2;{};{};{}m", r, g, b),
-
-            Color::Hex(_) => {
-                if let Some((r, g, b, _)) = self.to_rgba() {
-                    format!("\x1b[38;2;{};{};{}m", r, g, b)
-                } else {
-                    "\x1b[39m".to_string()
-                }
-            }
         }
     }
 
@@ -258,20 +213,120 @@ impl ColorConversion for Color {
             }
 
             Color::Rgba(r, g, b, _) => format!("\x1b[48;2;{};{};{}m", r, g, b),
+        }
+    }
+
+    // SGR 58 only takes indexed or RGB colors, so the basic 16 map to their palette index.
+    fn to_underline_ansi_code(&self) -> String {
+        let code = self.to_ansi_code();
+        let params = code.trim_start_matches("\x1b[").trim_end_matches('m');
+
+        if let Some(rest) = params.strip_prefix("38;") {
+            return format!("\x1b[58;{}m", rest);
+        }
+
+        match params.parse::<u8>() {
+            Ok(n @ 30..=37) => format!("\x1b[58;5;{}m", n - 30),
+            Ok(n @ 90..=97) => format!("\x1b[58;5;{}m", n - 90 + 8),
+            _ => Color::reset_underline_color().to_string(),
+        }
+    }
+
+    // The nearest xterm-256 index; indexed and named 256-color values keep their own index.
+    fn to_ansi256(&self) -> Option<u8> {
+        match *self {
+            Color::Indexed(n) => Some(n),
+            Color::Gray(g) => Some(232 + g.min(23)),
+            Color::Transparent => None,
+            _ => {
+                if let Some(index) = basic_index(*self) {
+                    return Some(index);
+                }
+
+                let code = self.to_ansi_code();
 
-            Color::Hex(ref hex) => {
-                if hex.len() == 6 {
-                    let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(0);
-                    let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(0);
-                    let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(0);
-                    format!("\x1b[48;2;{};{};{}m", r, g, b)
-                } else {
-                    "\x1b[49m".to_string()
+                match code.strip_prefix("\x1b[38;5;").and_then(|rest| rest.strip_suffix('m')) {
+                    Some(index) => index.parse().ok(),
+                    None => display_rgb(self).map(nearest_ansi256),
                 }
             }
         }
     }
 
+    // The nearest of the 16 basic colors, as a palette index (0–15).
+    fn to_ansi16(&self) -> Option<u8> {
+        match *self {
+            Color::Indexed(n) if n < 16 => Some(n),
+            Color::Transparent => None,
+            _ => basic_index(*self).or_else(|| display_rgb(self).map(nearest_ansi16)),
+        }
+    }
+
+    // The color to emit at `level`, or `None` when the terminal shows no color at all.
+    fn downsample(&self, level: ColorLevel) -> Option<Color> {
+        match level {
+            ColorLevel::None => None,
+            ColorLevel::TrueColor => Some(*self),
+            ColorLevel::Ansi256 => match *self {
+                Color::Rgb(..) | Color::Rgba(..) => self.to_ansi256().map(Color::Indexed),
+                _ => Some(*self),
+            },
+            ColorLevel::Ansi16 => match self.to_ansi16() {
+                Some(index) => Some(BASIC[index as usize]),
+                None => Some(*self),
+            },
+        }
+    }
+
+    fn to_ansi_code_for(&self, level: ColorLevel) -> String {
+        self.downsample(level).map(|color| color.to_ansi_code()).unwrap_or_default()
+    }
+
+    fn to_background_ansi_code_for(&self, level: ColorLevel) -> String {
+        self.downsample(level).map(|color| color.to_background_ansi_code()).unwrap_or_default()
+    }
+
+    // Underline colors need 256-color support, so 16-color terminals don't get them.
+    fn to_underline_ansi_code_for(&self, level: ColorLevel) -> String {
+        if level < ColorLevel::Ansi256 {
+            return String::new();
+        }
+
+        self.downsample(level).map(|color| color.to_underline_ansi_code()).unwrap_or_default()
+    }
+
+    fn to_hsl(&self) -> Option<Hsl> {
+        let (r, g, b) = unit_rgb(self)?;
+
+        Some(Hsl::from_rgb(r, g, b))
+    }
+
+    fn to_hsv(&self) -> Option<Hsv> {
+        let (r, g, b) = unit_rgb(self)?;
+
+        Some(Hsv::from_rgb(r, g, b))
+    }
+
+    fn to_lab(&self) -> Option<Lab> {
+        let (r, g, b) = unit_rgb(self)?;
+
+        Some(Lab::from_rgb(r, g, b))
+    }
+
+    fn to_lch(&self) -> Option<Lch> {
+        self.to_lab().map(|lab| lab.to_lch())
+    }
+
+    fn to_oklab(&self) -> Option<Oklab> {
+        let (r, g, b) = unit_rgb(self)?;
+
+        Some(Oklab::from_rgb(r, g, b))
+    }
+
+    fn to_oklch(&self) -> Option<Oklch> {
+        self.to_oklab().map(|oklab| oklab.to_oklch())
+    }
+
     fn indexed_to_rgba(index: u8) -> (f32, f32, f32, f32) {
         if index < 16 {
             // Standard ANSI colors
@@ -290,90 +345,111 @@ impl ColorConversion for Color {
             let g = (i / 6) % 6;
             let b = i % 6;
             let scale = [0, 95, 135, 175, 215, 255];
-            return (
+            (
                 scale[r as usize] as f32 / 255.0,
                 scale[g as usize] as f32 / 255.0,
                 scale[b as usize] as f32 / 255.0,
                 1.0,
-            );
+            )
         } else {
             let shade = 8 + (index - 232) * 10;
-            return (shade as f32 / 255.0, shade as f32 / 255.0, shade as f32 / 255.0, 1.0);
+            (shade as f32 / 255.0, shade as f32 / 255.0, shade as f32 / 255.0, 1.0)
         }
     }
 
     fn from_hex(hex: &str) -> Result<Color, String> {
-        let hex = hex.trim_start_matches('#');
-
-        match hex.len() {
-            6 => {
-                let r = u8::from_str_radix(&hex[0..2], 16).map_err(|_| "Invalid hex")?;
-                let g = u8::from_str_radix(&hex[2..4], 16).map_err(|_| "Invalid hex")?;
-                let b = u8::from_str_radix(&hex[4..6], 16).map_err(|_| "Invalid hex")?;
-                Ok(Color::Rgba(r, g, b, 255))
-            }
-            8 => {
-                let r = u8::from_str_radix(&hex[0..2], 16).map_err(|_| "Invalid hex")?;
-                let g = u8::from_str_radix(&hex[2..4], 16).map_err(|_| "Invalid hex")?;
-                let b = u8::from_str_radix(&hex[4..6], 16).map_err(|_| "Invalid hex")?;
-                let a = u8::from_str_radix(&hex[6..8], 16).map_err(|_| "Invalid hex")?;
-                Ok(Color::Rgba(r, g, b, a))
-            }
-            _ => Err("Invalid hex format. Use #RRGGBB or #RRGGBBAA.".to_string()),
-        }
+        Color::try_from_hex(hex.trim())
+            .ok_or_else(|| format!("Invalid hex color: '{}', expected #RGB, #RGBA, #RRGGBB or #RRGGBBAA", hex))
     }
 }
 
+// The displayed color as 0–1 floats, ignoring alpha.
+fn unit_rgb(color: &Color) -> Option<(f32, f32, f32)> {
+    let (r, g, b) = display_rgb(color)?;
+
+    Some((r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0))
+}
+
+// Our own names, which take priority over the CSS ones.
+const NAMES: [(&str, Color); 34] = [
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("white", Color::White),
+    ("black", Color::Black),
+    ("bright red", Color::BrightRed),
+    ("bright green", Color::BrightGreen),
+    ("bright yellow", Color::BrightYellow),
+    ("bright blue", Color::BrightBlue),
+    ("bright magenta", Color::BrightMagenta),
+    ("bright cyan", Color::BrightCyan),
+    ("bright white", Color::BrightWhite),
+    ("bright black", Color::BrightBlack),
+    ("orange", Color::Orange),
+    ("pink", Color::Pink),
+    ("teal", Color::Teal),
+    ("violet", Color::Violet),
+    ("indigo", Color::Indigo),
+    ("lime", Color::Lime),
+    ("turquoise", Color::Turquoise),
+    ("coral", Color::Coral),
+    ("crimson", Color::Crimson),
+    ("mint", Color::Mint),
+    ("gold", Color::Gold),
+    ("silver", Color::Silver),
+    ("bronze", Color::Bronze),
+    ("light gray", Color::LightGray),
+    ("dark gray", Color::DarkGray),
+    ("slate gray", Color::SlateGray),
+    ("charcoal", Color::Charcoal),
+    ("transparent", Color::Transparent),
+];
+
 impl FromStr for Color {
     type Err = String;
 
+    // Our own names come first, so `green`, `blue`, `cyan`, `teal`, `lime`, `gold` and `silver`
+    // give the named variants rather than their CSS values; `Color::from_css` has those.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.trim().to_lowercase();
-        let colors = vec![
-            ("red", Color::Red),
-            ("green", Color::Green),
-            ("yellow", Color::Yellow),
-            ("blue", Color::Blue),
-            ("magenta", Color::Magenta),
-            ("cyan", Color::Cyan),
-            ("white", Color::White),
-            ("black", Color::Black),
-            ("bright red", Color::BrightRed),
-            ("bright green", Color::BrightGreen),
-            ("bright yellow", Color::BrightYellow),
-            ("bright blue", Color::BrightBlue),
-            ("bright magenta", Color::BrightMagenta),
-            ("bright cyan", Color::BrightCyan),
-            ("bright white", Color::BrightWhite),
-            ("bright black", Color::BrightBlack),
-            ("orange", Color::Orange),
-            ("pink", Color::Pink),
-            ("teal", Color::Teal),
-            ("violet", Color::Violet),
-            ("indigo", Color::Indigo),
-            ("lime", Color::Lime),
-            ("turquoise", Color::Turquoise),
-            ("coral", Color::Coral),
-            ("crimson", Color::Crimson),
-            ("mint", Color::Mint),
-            ("gold", Color::Gold),
-            ("silver", Color::Silver),
-            ("bronze", Color::Bronze),
-            ("light gray", Color::LightGray),
-            ("dark gray", Color::DarkGray),
-            ("slate gray", Color::SlateGray),
-            ("charcoal", Color::Charcoal),
-            ("transparent", Color::Transparent),
-        ];
-
-        if let Some((_, color)) = colors.iter().find(|(name, _)| *name == s) {
+        let s = s.trim();
+        let lowercase = s.to_lowercase();
+
+        if let Some((_, color)) = NAMES.iter().find(|(name, _)| *name == lowercase) {
             return Ok(*color);
         }
 
-        if s.starts_with('#') && (s.len() == 7 || s.len() == 9) {
-            return Ok(Color::Hex(Box::leak(s.into_boxed_str())));
+        if let Some(color) = css::named(&lowercase) {
+            return Ok(color);
+        }
+
+        if s.starts_with('#') {
+            return Color::from_hex(s);
+        }
+
+        if let Some(color) = css::parse_function(s) {
+            return color;
         }
 
         Err(format!("Unknown color: '{}'", s))
     }
 }
+
+// Writes a name, `#rrggbb`, `#rrggbbaa`, `ansi(n)` or `gray(n)`, all of which `FromStr` reads back.
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if let Some((name, _)) = NAMES.iter().find(|(_, color)| color == self) {
+            return write!(f, "{}", name);
+        }
+
+        match *self {
+            Color::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+            Color::Rgba(r, g, b, a) => write!(f, "#{:02x}{:02x}{:02x}{:02x}", r, g, b, a),
+            Color::Indexed(n) => write!(f, "ansi({})", n),
+            Color::Gray(g) => write!(f, "gray({})", g.min(23)),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use crate::Color;
+use crate::spaces::normalize_hue;
+
+// The CSS Color Level 4 named colors, `grey` spellings included.
+const NAMED: [(&str, Color); 148] = [
+    ("aliceblue", Color::Rgb(240, 248, 255)),
+    ("antiquewhite", Color::Rgb(250, 235, 215)),
+    ("aqua", Color::Rgb(0, 255, 255)),
+    ("aquamarine", Color::Rgb(127, 255, 212)),
+    ("azure", Color::Rgb(240, 255, 255)),
+    ("beige", Color::Rgb(245, 245, 220)),
+    ("bisque", Color::Rgb(255, 228, 196)),
+    ("black", Color::Rgb(0, 0, 0)),
+    ("blanchedalmond", Color::Rgb(255, 235, 205)),
+    ("blue", Color::Rgb(0, 0, 255)),
+    ("blueviolet", Color::Rgb(138, 43, 226)),
+    ("brown", Color::Rgb(165, 42, 42)),
+    ("burlywood", Color::Rgb(222, 184, 135)),
+    ("cadetblue", Color::Rgb(95, 158, 160)),
+    ("chartreuse", Color::Rgb(127, 255, 0)),
+    ("chocolate", Color::Rgb(210, 105, 30)),
+    ("coral", Color::Rgb(255, 127, 80)),
+    ("cornflowerblue", Color::Rgb(100, 149, 237)),
+    ("cornsilk", Color::Rgb(255, 248, 220)),
+    ("crimson", Color::Rgb(220, 20, 60)),
+    ("cyan", Color::Rgb(0, 255, 255)),
+    ("darkblue", Color::Rgb(0, 0, 139)),
+    ("darkcyan", Color::Rgb(0, 139, 139)),
+    ("darkgoldenrod", Color::Rgb(184, 134, 11)),
+    ("darkgray", Color::Rgb(169, 169, 169)),
+    ("darkgreen", Color::Rgb(0, 100, 0)),
+    ("darkgrey", Color::Rgb(169, 169, 169)),
+    ("darkkhaki", Color::Rgb(189, 183, 107)),
+    ("darkmagenta", Color::Rgb(139, 0, 139)),
+    ("darkolivegreen", Color::Rgb(85, 107, 47)),
+    ("darkorange", Color::Rgb(255, 140, 0)),
+    ("darkorchid", Color::Rgb(153, 50, 204)),
+    ("darkred", Color::Rgb(139, 0, 0)),
+    ("darksalmon", Color::Rgb(233, 150, 122)),
+    ("darkseagreen", Color::Rgb(143, 188, 143)),
+    ("darkslateblue", Color::Rgb(72, 61, 139)),
+    ("darkslategray", Color::Rgb(47, 79, 79)),
+    ("darkslategrey", Color::Rgb(47, 79, 79)),
+    ("darkturquoise", Color::Rgb(0, 206, 209)),
+    ("darkviolet", Color::Rgb(148, 0, 211)),
+    ("deeppink", Color::Rgb(255, 20, 147)),
+    ("deepskyblue", Color::Rgb(0, 191, 255)),
+    ("dimgray", Color::Rgb(105, 105, 105)),
+    ("dimgrey", Color::Rgb(105, 105, 105)),
+    ("dodgerblue", Color::Rgb(30, 144, 255)),
+    ("firebrick", Color::Rgb(178, 34, 34)),
+    ("floralwhite", Color::Rgb(255, 250, 240)),
+    ("forestgreen", Color::Rgb(34, 139, 34)),
+    ("fuchsia", Color::Rgb(255, 0, 255)),
+    ("gainsboro", Color::Rgb(220, 220, 220)),
+    ("ghostwhite", Color::Rgb(248, 248, 255)),
+    ("gold", Color::Rgb(255, 215, 0)),
+    ("goldenrod", Color::Rgb(218, 165, 32)),
+    ("gray", Color::Rgb(128, 128, 128)),
+    ("green", Color::Rgb(0, 128, 0)),
+    ("greenyellow", Color::Rgb(173, 255, 47)),
+    ("grey", Color::Rgb(128, 128, 128)),
+    ("honeydew", Color::Rgb(240, 255, 240)),
+    ("hotpink", Color::Rgb(255, 105, 180)),
+    ("indianred", Color::Rgb(205, 92, 92)),
+    ("indigo", Color::Rgb(75, 0, 130)),
+    ("ivory", Color::Rgb(255, 255, 240)),
+    ("khaki", Color::Rgb(240, 230, 140)),
+    ("lavender", Color::Rgb(230, 230, 250)),
+    ("lavenderblush", Color::Rgb(255, 240, 245)),
+    ("lawngreen", Color::Rgb(124, 252, 0)),
+    ("lemonchiffon", Color::Rgb(255, 250, 205)),
+    ("lightblue", Color::Rgb(173, 216, 230)),
+    ("lightcoral", Color::Rgb(240, 128, 128)),
+    ("lightcyan", Color::Rgb(224, 255, 255)),
+    ("lightgoldenrodyellow", Color::Rgb(250, 250, 210)),
+    ("lightgray", Color::Rgb(211, 211, 211)),
+    ("lightgreen", Color::Rgb(144, 238, 144)),
+    ("lightgrey", Color::Rgb(211, 211, 211)),
+    ("lightpink", Color::Rgb(255, 182, 193)),
+    ("lightsalmon", Color::Rgb(255, 160, 122)),
+    ("lightseagreen", Color::Rgb(32, 178, 170)),
+    ("lightskyblue", Color::Rgb(135, 206, 250)),
+    ("lightslategray", Color::Rgb(119, 136, 153)),
+    ("lightslategrey", Color::Rgb(119, 136, 153)),
+    ("lightsteelblue", Color::Rgb(176, 196, 222)),
+    ("lightyellow", Color::Rgb(255, 255, 224)),
+    ("lime", Color::Rgb(0, 255, 0)),
+    ("limegreen", Color::Rgb(50, 205, 50)),
+    ("linen", Color::Rgb(250, 240, 230)),
+    ("magenta", Color::Rgb(255, 0, 255)),
+    ("maroon", Color::Rgb(128, 0, 0)),
+    ("mediumaquamarine", Color::Rgb(102, 205, 170)),
+    ("mediumblue", Color::Rgb(0, 0, 205)),
+    ("mediumorchid", Color::Rgb(186, 85, 211)),
+    ("mediumpurple", Color::Rgb(147, 112, 219)),
+    ("mediumseagreen", Color::Rgb(60, 179, 113)),
+    ("mediumslateblue", Color::Rgb(123, 104, 238)),
+    ("mediumspringgreen", Color::Rgb(0, 250, 154)),
+    ("mediumturquoise", Color::Rgb(72, 209, 204)),
+    ("mediumvioletred", Color::Rgb(199, 21, 133)),
+    ("midnightblue", Color::Rgb(25, 25, 112)),
+    ("mintcream", Color::Rgb(245, 255, 250)),
+    ("mistyrose", Color::Rgb(255, 228, 225)),
+    ("moccasin", Color::Rgb(255, 228, 181)),
+    ("navajowhite", Color::Rgb(255, 222, 173)),
+    ("navy", Color::Rgb(0, 0, 128)),
+    ("oldlace", Color::Rgb(253, 245, 230)),
+    ("olive", Color::Rgb(128, 128, 0)),
+    ("olivedrab", Color::Rgb(107, 142, 35)),
+    ("orange", Color::Rgb(255, 165, 0)),
+    ("orangered", Color::Rgb(255, 69, 0)),
+    ("orchid", Color::Rgb(218, 112, 214)),
+    ("palegoldenrod", Color::Rgb(238, 232, 170)),
+    ("palegreen", Color::Rgb(152, 251, 152)),
+    ("paleturquoise", Color::Rgb(175, 238, 238)),
+    ("palevioletred", Color::Rgb(219, 112, 147)),
+    ("papayawhip", Color::Rgb(255, 239, 213)),
+    ("peachpuff", Color::Rgb(255, 218, 185)),
+    ("peru", Color::Rgb(205, 133, 63)),
+    ("pink", Color::Rgb(255, 192, 203)),
+    ("plum", Color::Rgb(221, 160, 221)),
+    ("powderblue", Color::Rgb(176, 224, 230)),
+    ("purple", Color::Rgb(128, 0, 128)),
+    ("rebeccapurple", Color::Rgb(102, 51, 153)),
+    ("red", Color::Rgb(255, 0, 0)),
+    ("rosybrown", Color::Rgb(188, 143, 143)),
+    ("royalblue", Color::Rgb(65, 105, 225)),
+    ("saddlebrown", Color::Rgb(139, 69, 19)),
+    ("salmon", Color::Rgb(250, 128, 114)),
+    ("sandybrown", Color::Rgb(244, 164, 96)),
+    ("seagreen", Color::Rgb(46, 139, 87)),
+    ("seashell", Color::Rgb(255, 245, 238)),
+    ("sienna", Color::Rgb(160, 82, 45)),
+    ("silver", Color::Rgb(192, 192, 192)),
+    ("skyblue", Color::Rgb(135, 206, 235)),
+    ("slateblue", Color::Rgb(106, 90, 205)),
+    ("slategray", Color::Rgb(112, 128, 144)),
+    ("slategrey", Color::Rgb(112, 128, 144)),
+    ("snow", Color::Rgb(255, 250, 250)),
+    ("springgreen", Color::Rgb(0, 255, 127)),
+    ("steelblue", Color::Rgb(70, 130, 180)),
+    ("tan", Color::Rgb(210, 180, 140)),
+    ("teal", Color::Rgb(0, 128, 128)),
+    ("thistle", Color::Rgb(216, 191, 216)),
+    ("tomato", Color::Rgb(255, 99, 71)),
+    ("turquoise", Color::Rgb(64, 224, 208)),
+    ("violet", Color::Rgb(238, 130, 238)),
+    ("wheat", Color::Rgb(245, 222, 179)),
+    ("white", Color::Rgb(255, 255, 255)),
+    ("whitesmoke", Color::Rgb(245, 245, 245)),
+    ("yellow", Color::Rgb(255, 255, 0)),
+    ("yellowgreen", Color::Rgb(154, 205, 50)),
+];
+
+pub(crate) fn named(name: &str) -> Option<Color> {
+    NAMED.iter().find(|(candidate, _)| *candidate == name).map(|(_, color)| *color)
+}
+
+impl Color {
+    // Parses a color the way CSS does, so `green` is `#008000` rather than `Color::Green`
+    // as with `str::parse`. Takes CSS names, hex codes and the functional notations.
+    pub fn from_css(s: &str) -> Result<Color, String> {
+        let s = s.trim();
+
+        if let Some(color) = named(&s.to_lowercase()) {
+            return Ok(color);
+        }
+
+        if s.starts_with('#') {
+            return Color::try_from_hex(s).ok_or_else(|| format!("Invalid hex color: '{}'", s));
+        }
+
+        parse_function(s).unwrap_or_else(|| Err(format!("Unknown CSS color: '{}'", s)))
+    }
+}
+
+// Parses the functional notations: `rgb()`/`rgba()`, `hsl()`/`hsla()`, `hwb()`, `oklch()`,
+// and `ansi(n)`/`gray(n)` for the indexed variants. `None` when `s` isn't a function call at all.
+// Expects trimmed input; errors quote it as given.
+pub(crate) fn parse_function(s: &str) -> Option<Result<Color, String>> {
+    let lowercase = s.to_lowercase();
+    let (name, rest) = lowercase.split_once('(')?;
+    let name = name.trim();
+
+    if !name.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let Some(inner) = rest.strip_suffix(')') else {
+        return Some(Err(format!("Missing ')' in '{}'", s)));
+    };
+
+    let args = match Arguments::split(inner) {
+        Ok(args) => args,
+        Err(e) => return Some(Err(format!("{} in '{}'", e, s))),
+    };
+
+    let color = match name {
+        "rgb" | "rgba" => rgb(&args),
+        "hsl" | "hsla" => hsl(&args),
+        "hwb" => hwb(&args),
+        "oklch" => oklch(&args),
+        "ansi" => index(&args, 255).map(Color::Indexed),
+        "gray" | "grey" => index(&args, 23).map(Color::Gray),
+        _ => Err(format!("Unknown color function '{}()'", s[..s.find('(').unwrap_or(0)].trim())),
+    };
+
+    Some(color.map_err(|e| format!("{} in '{}'", e, s)))
+}
+
+struct Arguments<'a> {
+    channels: Vec<&'a str>,
+    alpha: Option<&'a str>,
+}
+
+impl<'a> Arguments<'a> {
+    // Both the legacy `rgb(1, 2, 3, 0.5)` and the modern `rgb(1 2 3 / 50%)` syntax.
+    fn split(inner: &'a str) -> Result<Self, String> {
+        let (main, alpha) = match inner.split_once('/') {
+            Some((main, alpha)) => (main, Some(alpha.trim())),
+            None => (inner, None),
+        };
+
+        if alpha.is_some_and(|alpha| alpha.is_empty() || alpha.contains(['/', ',', ' '])) {
+            return Err("Expected a single alpha value after '/'".to_string());
+        }
+
+        let legacy = main.contains(',');
+
+        let mut channels: Vec<&str> = if legacy {
+            if alpha.is_some() {
+                return Err("Can't mix commas with '/' for alpha".to_string());
+            }
+
+            let channels: Vec<&str> = main.split(',').map(str::trim).collect();
+
+            if channels.iter().any(|channel| channel.is_empty()) {
+                return Err("Empty value between commas".to_string());
+            }
+
+            channels
+        } else {
+            main.split_whitespace().collect()
+        };
+
+        let alpha = if legacy && channels.len() == 4 { channels.pop() } else { alpha };
+
+        Ok(Self { channels, alpha })
+    }
+
+    fn expect(&self, count: usize, names: &str) -> Result<(), String> {
+        if self.channels.len() != count {
+            return Err(format!("Expected {} values ({}) but found {}", count, names, self.channels.len()));
+        }
+
+        Ok(())
+    }
+
+    fn alpha(&self) -> Result<u8, String> {
+        match self.alpha {
+            Some(alpha) => Ok(unit(component(alpha, 1.0, "alpha")?)),
+            None => Ok(255),
+        }
+    }
+}
+
+fn unit(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn number(token: &str, what: &str) -> Result<f32, String> {
+    token
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| format!("Invalid {} '{}'", what, token))
+}
+
+// A number, or a percentage of `full`. `none` counts as zero, as in CSS.
+fn component(token: &str, full: f32, what: &str) -> Result<f32, String> {
+    if token == "none" {
+        return Ok(0.0);
+    }
+
+    match token.strip_suffix('%') {
+        Some(percent) => Ok(number(percent, what)? / 100.0 * full),
+        None => number(token, what),
+    }
+}
+
+// A percentage, or a bare number on the same 0–100 scale, as a fraction.
+fn percentage(token: &str, what: &str) -> Result<f32, String> {
+    Ok(component(token, 100.0, what)? / 100.0)
+}
+
+fn hue(token: &str) -> Result<f32, String> {
+    if token == "none" {
+        return Ok(0.0);
+    }
+
+    let units = [("deg", 1.0), ("grad", 0.9), ("rad", 180.0 / std::f32::consts::PI), ("turn", 360.0)];
+
+    for (unit, scale) in units {
+        if let Some(value) = token.strip_suffix(unit) {
+            return Ok(normalize_hue(number(value, "hue")? * scale));
+        }
+    }
+
+    Ok(normalize_hue(number(token, "hue")?))
+}
+
+fn rgb(args: &Arguments) -> Result<Color, String> {
+    args.expect(3, "red, green, blue")?;
+
+    let channel = |i: usize, what: &str| -> Result<u8, String> {
+        Ok(component(args.channels[i], 255.0, what)?.clamp(0.0, 255.0).round() as u8)
+    };
+
+    let color = Color::Rgb(channel(0, "red")?, channel(1, "green")?, channel(2, "blue")?);
+
+    Ok(color.with_alpha(args.alpha()?))
+}
+
+fn hsl(args: &Arguments) -> Result<Color, String> {
+    args.expect(3, "hue, saturation, lightness")?;
+
+    let h = hue(args.channels[0])?;
+    let s = percentage(args.channels[1], "saturation")?;
+    let l = percentage(args.channels[2], "lightness")?;
+
+    Ok(Color::hsl(h, s, l).with_alpha(args.alpha()?))
+}
+
+fn hwb(args: &Arguments) -> Result<Color, String> {
+    args.expect(3, "hue, whiteness, blackness")?;
+
+    let h = hue(args.channels[0])?;
+    let mut w = percentage(args.channels[1], "whiteness")?.clamp(0.0, 1.0);
+    let mut b = percentage(args.channels[2], "blackness")?.clamp(0.0, 1.0);
+
+    // Whiteness and blackness past 100% together are scaled down to a gray
+    if w + b > 1.0 {
+        let sum = w + b;
+        w /= sum;
+        b /= sum;
+    }
+
+    let v = 1.0 - b;
+    let s = if v > 0.0 { 1.0 - w / v } else { 0.0 };
+
+    Ok(Color::hsv(h, s, v).with_alpha(args.alpha()?))
+}
+
+fn oklch(args: &Arguments) -> Result<Color, String> {
+    args.expect(3, "lightness, chroma, hue")?;
+
+    // 100% is a lightness of 1 and a chroma of 0.4
+    let l = component(args.channels[0], 1.0, "lightness")?;
+    let c = component(args.channels[1], 0.4, "chroma")?.max(0.0);
+    let h = hue(args.channels[2])?;
+
+    Ok(Color::oklch(l, c, h).with_alpha(args.alpha()?))
+}
+
+fn index(args: &Arguments, max: u8) -> Result<u8, String> {
+    if args.alpha.is_some() {
+        return Err("Indexed colors take no alpha".to_string());
+    }
+
+    args.expect(1, "index")?;
+
+    let token = args.channels[0];
+
+    match token.parse::<u16>() {
+        Ok(n) if n <= max as u16 => Ok(n as u8),
+        Ok(n) => Err(format!("Index {} is out of range 0–{}", n, max)),
+        Err(_) => Err(format!("Invalid index '{}'", token)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_round_trips_through_from_str() {
+        let colors = [
+            Color::Green,
+            Color::BrightMagenta,
+            Color::Transparent,
+            Color::Rgb(18, 52, 86),
+            Color::Rgba(18, 52, 86, 128),
+            Color::Indexed(200),
+            Color::Gray(7),
+        ];
+
+        for color in colors {
+            assert_eq!(color.to_string().parse::<Color>(), Ok(color), "{}", color);
+        }
+    }
+
+    #[test]
+    fn functions_take_alpha_after_a_slash() {
+        assert_eq!("rgb(255 0 0 / 50%)".parse(), Ok(Color::Rgba(255, 0, 0, 128)));
+        assert_eq!("rgba(255, 0, 0, 0.5)".parse(), Ok(Color::Rgba(255, 0, 0, 128)));
+        assert_eq!("hsl(120deg 100% 50% / 100%)".parse(), Ok(Color::Rgb(0, 255, 0)));
+        assert!("rgb(255, 0 / 50%)".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn css_names_win_only_in_from_css() {
+        assert_eq!("green".parse(), Ok(Color::Green));
+        assert_eq!(Color::from_css("Green"), Ok(Color::Rgb(0, 128, 0)));
+        assert_eq!(Color::from_css("rebeccapurple"), Ok(Color::Rgb(102, 51, 153)));
+        assert!(Color::from_css("bright red").is_err());
+    }
+
+    #[test]
+    fn errors_quote_the_input_as_typed() {
+        assert_eq!("NotAColor".parse::<Color>(), Err("Unknown color: 'NotAColor'".to_string()));
+        assert!("RGB(1, 2)".parse::<Color>().unwrap_err().contains("'RGB(1, 2)'"));
+    }
+}
@@ -0,0 +1,272 @@
+use crate::{Color, ColorLevel};
+use crate::ansi::{tokens, Token};
+use crate::conversion::ColorConversion;
+use crate::spaces::{normalize_hue, Oklch};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ColorSpace {
+    #[default]
+    Rgb,
+    Oklch,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Direction {
+    // Across each line, aligned in columns over the widest line
+    #[default]
+    Horizontal,
+    // Down the lines, one color per line
+    Vertical,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    stops: Vec<Color>,
+    space: ColorSpace,
+    direction: Direction,
+}
+
+impl Gradient {
+    pub fn new(stops: &[Color], space: ColorSpace) -> Self {
+        Self {
+            stops: stops.to_vec(),
+            space,
+            direction: Direction::Horizontal,
+        }
+    }
+
+    // Hues all the way around at even lightness and chroma.
+    pub fn rainbow() -> Self {
+        let stops: Vec<Color> = (0..6).map(|i| Color::oklch(0.75, 0.15, 30.0 + i as f32 * 60.0)).collect();
+
+        Self::new(&stops, ColorSpace::Oklch)
+    }
+
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn vertical(self) -> Self {
+        self.direction(Direction::Vertical)
+    }
+
+    // The color at `t` in 0–1 along the stops.
+    pub fn at(&self, t: f32) -> Color {
+        match self.stops.len() {
+            0 => return Color::Transparent,
+            1 => return self.stops[0],
+            _ => {}
+        }
+
+        let position = t.clamp(0.0, 1.0) * (self.stops.len() - 1) as f32;
+        let index = (position.floor() as usize).min(self.stops.len() - 2);
+        let (from, to) = (self.stops[index], self.stops[index + 1]);
+
+        interpolate(from, to, position - index as f32, self.space)
+    }
+
+    // `count` evenly spaced colors from the first stop to the last.
+    pub fn colors(&self, count: usize) -> Vec<Color> {
+        (0..count)
+            .map(|i| self.at(if count > 1 { i as f32 / (count - 1) as f32 } else { 0.0 }))
+            .collect()
+    }
+
+    pub fn paint(&self, text: &str) -> String {
+        self.paint_for(text, ColorLevel::TrueColor)
+    }
+
+    // Colors every grapheme, downsampled to `level` so other terminals get the nearest palette color.
+    // Escape sequences already in `text` are kept as they are and take no column.
+    pub fn paint_for(&self, text: &str, level: ColorLevel) -> String {
+        if level == ColorLevel::None || self.stops.is_empty() {
+            return text.to_string();
+        }
+
+        let lines = pieces(text);
+        let width = lines
+            .iter()
+            .map(|line| line.iter().filter(|piece| matches!(piece, Token::Text(_))).count())
+            .max()
+            .unwrap_or(0);
+        let height = lines.len();
+
+        let colors = match self.direction {
+            Direction::Horizontal => self.colors(width),
+            Direction::Vertical => self.colors(height),
+        };
+
+        let mut out = String::with_capacity(text.len() * 8);
+        let mut active = String::new();
+        let mut painted = false;
+
+        for (row, line) in lines.iter().enumerate() {
+            if row > 0 {
+                out.push('\n');
+            }
+
+            let mut column = 0;
+
+            for piece in line {
+                let grapheme = match piece {
+                    Token::Escape(escape) => {
+                        // The sequence may reset or replace our color, so repeat it afterwards
+                        out.push_str(escape);
+                        active.clear();
+                        continue;
+                    }
+                    Token::Text(grapheme) => grapheme,
+                };
+
+                if !grapheme.trim().is_empty() {
+                    let color = match self.direction {
+                        Direction::Horizontal => colors[column],
+                        Direction::Vertical => colors[row],
+                    };
+
+                    let code = color.to_ansi_code_for(level);
+
+                    if code != active {
+                        out.push_str(&code);
+                        active = code;
+                        painted = true;
+                    }
+                }
+
+                out.push_str(grapheme);
+                column += 1;
+            }
+        }
+
+        if painted {
+            out.push_str(Color::reset_foreground());
+        }
+
+        out
+    }
+}
+
+// The lines of `text` as escape sequences and single graphemes.
+fn pieces(text: &str) -> Vec<Vec<Token<'_>>> {
+    let mut lines = vec![Vec::new()];
+
+    for token in tokens(text) {
+        match token {
+            Token::Escape(escape) => lines.last_mut().unwrap().push(Token::Escape(escape)),
+            Token::Text(text) => {
+                for (i, line) in text.split('\n').enumerate() {
+                    if i > 0 {
+                        lines.push(Vec::new());
+                    }
+
+                    lines.last_mut().unwrap().extend(graphemes(line).into_iter().map(Token::Text));
+                }
+            }
+        }
+    }
+
+    lines
+}
+
+fn interpolate(from: Color, to: Color, t: f32, space: ColorSpace) -> Color {
+    match space {
+        ColorSpace::Rgb => from.mix(to, t),
+        ColorSpace::Oklch => match (from.to_oklch(), to.to_oklch()) {
+            (Some(a), Some(b)) => {
+                // Grays have no hue, so they take the other end's to avoid sweeping through unrelated colors
+                let (ha, hb) = match (a.c < 1e-3, b.c < 1e-3) {
+                    (true, false) => (b.h, b.h),
+                    (false, true) => (a.h, a.h),
+                    _ => (a.h, b.h),
+                };
+
+                // Shortest way around the hue circle
+                let mut delta = hb - ha;
+
+                if delta > 180.0 {
+                    delta -= 360.0;
+                } else if delta < -180.0 {
+                    delta += 360.0;
+                }
+
+                Oklch {
+                    l: a.l + (b.l - a.l) * t,
+                    c: a.c + (b.c - a.c) * t,
+                    h: normalize_hue(ha + delta * t),
+                }
+                .into()
+            }
+            _ => from.mix(to, t),
+        },
+    }
+}
+
+pub(crate) fn is_extending(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x20D0..=0x20FF
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF
+            | 0xE0020..=0xE007F
+            | 0x200D
+    )
+}
+
+// An approximation of extended grapheme clusters: combining marks, variation selectors,
+// skin tones and zero-width joiner sequences stay with the character they modify.
+pub(crate) fn graphemes(text: &str) -> Vec<&str> {
+    let mut clusters = Vec::new();
+    let mut start = 0;
+    let mut joined = false;
+    let mut regional = false;
+
+    for (i, c) in text.char_indices() {
+        // Flags are pairs of regional indicator symbols
+        let is_regional = matches!(c as u32, 0x1F1E6..=0x1F1FF);
+
+        let boundary = !(i <= start || is_extending(c) || joined || is_regional && regional);
+
+        if boundary {
+            clusters.push(&text[start..i]);
+            start = i;
+        }
+
+        // Only a cluster holding a single regional indicator can take a second one
+        regional = (boundary || i == 0) && is_regional;
+
+        joined = c == '\u{200D}';
+    }
+
+    if start < text.len() {
+        clusters.push(&text[start..]);
+    }
+
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strip_ansi;
+
+    #[test]
+    fn existing_escapes_pass_through() {
+        let gradient = Gradient::new(&[Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255)], ColorSpace::Rgb);
+        let painted = gradient.paint("\x1b[1mab\x1b[0mc");
+
+        assert!(painted.starts_with("\x1b[1m\x1b[38;2;255;0;0ma"));
+        assert!(painted.contains("\x1b[0m\x1b[38;2;0;0;255mc"));
+        assert_eq!(strip_ansi(&painted), "abc");
+    }
+
+    #[test]
+    fn flags_and_joiners_stay_whole() {
+        assert_eq!(graphemes("🇫🇷🇩🇪"), ["🇫🇷", "🇩🇪"]);
+        assert_eq!(graphemes("e\u{301}👩\u{200D}💻x"), ["e\u{301}", "👩\u{200D}💻", "x"]);
+    }
+}
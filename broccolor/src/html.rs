@@ -0,0 +1,301 @@
+use crate::{parse_ansi, Attributes, Color, ColorConversion, Style, StyledText};
+use crate::palette::{basic_index, BASIC};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum HtmlMode {
+    // Every span carries its own `style` attribute, so the HTML needs no stylesheet
+    #[default]
+    Inline,
+    // The 16 basic colors and the attributes become `ansi-*` classes, see `html_stylesheet`.
+    // Other colors stay inline.
+    Classes,
+}
+
+// The text decorations, with the `text-decoration-line` and `text-decoration-style` for each.
+const DECORATIONS: [(Attributes, &str, &str); 7] = [
+    (Attributes::UNDERLINE, "underline", "solid"),
+    (Attributes::DOUBLE_UNDERLINE, "underline", "double"),
+    (Attributes::CURLY_UNDERLINE, "underline", "wavy"),
+    (Attributes::DOTTED_UNDERLINE, "underline", "dotted"),
+    (Attributes::DASHED_UNDERLINE, "underline", "dashed"),
+    (Attributes::STRIKETHROUGH, "line-through", "solid"),
+    (Attributes::OVERLINE, "overline", "solid"),
+];
+
+// Attributes that map to a single CSS declaration. Blink has no CSS equivalent and is left out.
+const FONT: [(Attributes, &str); 4] = [
+    (Attributes::BOLD, "font-weight: bold"),
+    (Attributes::DIM, "opacity: 0.5"),
+    (Attributes::ITALIC, "font-style: italic"),
+    (Attributes::HIDDEN, "visibility: hidden"),
+];
+
+#[derive(Copy, Clone)]
+enum Paint {
+    Color(Color),
+    // A CSS keyword for the page's own colors
+    System(&'static str),
+}
+
+impl Paint {
+    fn css(&self) -> Option<String> {
+        match self {
+            Paint::Color(color) => color.to_css(),
+            Paint::System(keyword) => Some(keyword.to_string()),
+        }
+    }
+
+    // `ansi-red`, `ansi-bright-black` and so on, for the 16 basic colors only.
+    fn class(&self) -> Option<String> {
+        match self {
+            Paint::Color(color) => basic_index(*color).map(|_| format!("ansi-{}", color.to_string().replace(' ', "-"))),
+            Paint::System(_) => None,
+        }
+    }
+}
+
+// The text and background colors as shown. `Transparent` is the terminal default, which here is
+// whatever the page uses, and reverse video swaps the two, with the system colors standing in for
+// the defaults.
+fn paints(style: &Style) -> (Option<Paint>, Option<Paint>) {
+    let paint = |color: Option<Color>| color.filter(|color| *color != Color::Transparent).map(Paint::Color);
+    let (fg, bg) = (paint(style.fg), paint(style.bg));
+
+    if style.attrs.contains(Attributes::REVERSE) {
+        (
+            Some(bg.unwrap_or(Paint::System("Canvas"))),
+            Some(fg.unwrap_or(Paint::System("CanvasText"))),
+        )
+    } else {
+        (fg, bg)
+    }
+}
+
+// `Transparent` leaves the underline in the text color, so it needs no declaration.
+fn underline_css(style: &Style) -> Option<String> {
+    style.underline_color.filter(|color| *color != Color::Transparent).and_then(|color| color.to_css())
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+fn span(classes: &[String], declarations: &[String], content: &str) -> String {
+    if classes.is_empty() && declarations.is_empty() {
+        return content.to_string();
+    }
+
+    let mut out = String::from("<span");
+
+    if !classes.is_empty() {
+        out.push_str(&format!(" class=\"{}\"", classes.join(" ")));
+    }
+
+    if !declarations.is_empty() {
+        out.push_str(&format!(" style=\"{}\"", escape_html(&declarations.join("; "))));
+    }
+
+    out.push('>');
+    out.push_str(content);
+    out.push_str("</span>");
+    out
+}
+
+fn render_inline(style: &Style, content: &str) -> String {
+    let mut declarations = Vec::new();
+    let (fg, bg) = paints(style);
+
+    if let Some(css) = fg.and_then(|fg| fg.css()) {
+        declarations.push(format!("color: {}", css));
+    }
+
+    if let Some(css) = bg.and_then(|bg| bg.css()) {
+        declarations.push(format!("background-color: {}", css));
+    }
+
+    for (attribute, declaration) in FONT {
+        if style.attrs.contains(attribute) {
+            declarations.push(declaration.to_string());
+        }
+    }
+
+    let decorations: Vec<_> = DECORATIONS.iter().filter(|(attribute, _, _)| style.attrs.contains(*attribute)).collect();
+
+    if !decorations.is_empty() {
+        let mut lines: Vec<&str> = decorations.iter().map(|(_, line, _)| *line).collect();
+        lines.dedup();
+
+        declarations.push(format!("text-decoration-line: {}", lines.join(" ")));
+
+        // One style covers every line, so the underline's wins
+        if let Some((_, _, kind)) = decorations.first().filter(|(_, _, kind)| *kind != "solid") {
+            declarations.push(format!("text-decoration-style: {}", kind));
+        }
+
+        if let Some(css) = underline_css(style) {
+            declarations.push(format!("text-decoration-color: {}", css));
+        }
+    }
+
+    span(&[], &declarations, content)
+}
+
+// Decorations on nested elements add up in CSS, so each gets its own span
+// rather than one class overriding another's `text-decoration`.
+fn render_classes(style: &Style, content: &str) -> String {
+    let mut content = content.to_string();
+    let underline_color = underline_css(style);
+
+    for (attribute, line, _) in DECORATIONS.iter().rev() {
+        if style.attrs.contains(*attribute) {
+            let declarations: Vec<String> = match (&underline_color, *line) {
+                (Some(css), "underline") => vec![format!("text-decoration-color: {}", css)],
+                _ => Vec::new(),
+            };
+
+            content = span(&[class_name(*attribute)], &declarations, &content);
+        }
+    }
+
+    let mut classes = Vec::new();
+    let mut declarations = Vec::new();
+    let (fg, bg) = paints(style);
+
+    if let Some(fg) = fg {
+        match fg.class() {
+            Some(class) => classes.push(class),
+            None => declarations.extend(fg.css().map(|css| format!("color: {}", css))),
+        }
+    }
+
+    if let Some(bg) = bg {
+        match bg.class() {
+            Some(class) => classes.push(class.replacen("ansi-", "ansi-bg-", 1)),
+            None => declarations.extend(bg.css().map(|css| format!("background-color: {}", css))),
+        }
+    }
+
+    for (attribute, _) in FONT {
+        if style.attrs.contains(attribute) {
+            classes.push(class_name(attribute));
+        }
+    }
+
+    span(&classes, &declarations, &content)
+}
+
+fn class_name(attribute: Attributes) -> String {
+    format!("ansi-{}", attribute.names().concat())
+}
+
+// The rules for `HtmlMode::Classes`, with the basic colors as inline styles give them.
+pub fn html_stylesheet() -> String {
+    let mut css = String::new();
+
+    for (attribute, declaration) in FONT {
+        css.push_str(&format!(".{} {{ {}; }}\n", class_name(attribute), declaration));
+    }
+
+    for (attribute, line, kind) in DECORATIONS {
+        css.push_str(&format!(".{} {{ text-decoration: {} {}; }}\n", class_name(attribute), line, kind));
+    }
+
+    for color in BASIC {
+        let paint = Paint::Color(color);
+
+        if let (Some(class), Some(value)) = (paint.class(), paint.css()) {
+            css.push_str(&format!(".{} {{ color: {}; }}\n", class, value));
+            css.push_str(&format!(".{} {{ background-color: {}; }}\n", class.replacen("ansi-", "ansi-bg-", 1), value));
+        }
+    }
+
+    css
+}
+
+// HTML for the spans, with the text escaped. Whitespace and line breaks are kept as they are,
+// so put the result inside a `<pre>` or an element with `white-space: pre`.
+pub fn spans_to_html(spans: &[(Style, String)], mode: HtmlMode) -> String {
+    spans
+        .iter()
+        .map(|(style, text)| {
+            let content = escape_html(text);
+
+            match mode {
+                HtmlMode::Inline => render_inline(style, &content),
+                HtmlMode::Classes => render_classes(style, &content),
+            }
+        })
+        .collect()
+}
+
+// HTML for text holding SGR sequences, such as captured command output.
+pub fn ansi_to_html(text: &str, mode: HtmlMode) -> String {
+    spans_to_html(&parse_ansi(text), mode)
+}
+
+impl StyledText {
+    pub fn to_html(&self, mode: HtmlMode) -> String {
+        spans_to_html(self.spans(), mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_use_the_terminal_palette() {
+        assert_eq!(Color::Indexed(196).to_rgba_u8(), Some((255, 0, 0, 255)));
+        assert_eq!(Color::Indexed(196).to_css().as_deref(), Some("rgb(255, 0, 0)"));
+        assert_eq!(Color::Gray(0).to_css().as_deref(), Some("rgb(8, 8, 8)"));
+        assert_eq!(Color::Rgba(0, 0, 255, 51).to_css().as_deref(), Some("rgba(0, 0, 255, 0.2)"));
+        assert_eq!(Paint::Color(Color::Red).css().as_deref(), Some("rgb(128, 0, 0)"));
+    }
+
+    #[test]
+    fn classes_cover_basic_colors_and_attributes() {
+        let bold_red = Style::new().fg(Color::Red).bg(Color::BrightBlue).bold();
+        let underlined = Style::new().fg(Color::Rgb(1, 2, 3)).underline().underline_color(Color::Indexed(196));
+
+        assert_eq!(
+            spans_to_html(&[(bold_red, "a".to_string()), (underlined, "b".to_string())], HtmlMode::Classes),
+            concat!(
+                "<span class=\"ansi-red ansi-bg-bright-blue ansi-bold\">a</span>",
+                "<span style=\"color: rgb(1, 2, 3)\">",
+                "<span class=\"ansi-underline\" style=\"text-decoration-color: rgb(255, 0, 0)\">b</span></span>",
+            ),
+        );
+    }
+
+    #[test]
+    fn stylesheet_has_a_rule_for_every_class() {
+        let css = html_stylesheet();
+
+        assert!(css.contains(".ansi-bold { font-weight: bold; }\n"));
+        assert!(css.contains(".ansi-curly-underline { text-decoration: underline wavy; }\n"));
+        assert!(css.contains(".ansi-red { color: rgb(128, 0, 0); }\n"));
+        assert!(css.contains(".ansi-bg-bright-red { background-color: rgb(255, 0, 0); }\n"));
+        assert_eq!(css.lines().count(), FONT.len() + DECORATIONS.len() + 2 * BASIC.len());
+    }
+
+    #[test]
+    fn ansi_text_becomes_escaped_spans() {
+        assert_eq!(
+            ansi_to_html("\x1b[38;5;196m<b>\x1b[0m", HtmlMode::Inline),
+            "<span style=\"color: rgb(255, 0, 0)\">&lt;b&gt;</span>",
+        );
+    }
+}
@@ -1,6 +1,30 @@
+mod accessibility;
+mod ansi;
 pub mod colors;
 mod conversion;
 mod colorizing;
+mod css;
+mod gradient;
+mod html;
+mod operations;
+mod palette;
+#[cfg(feature = "serde")]
+mod serialize;
+mod spaces;
+mod span;
+mod style;
+mod support;
+mod theme;
 
+pub use accessibility::{best_text_color_for, ensure_contrast, VisionDeficiency};
+pub use ansi::{parse_ansi, render_ansi, slice, strip_ansi, truncate, visible_width};
 pub use colors::Color;
-pub use colorizing::{ColoredText, TextStyle};
\ No newline at end of file
+pub use conversion::ColorConversion;
+pub use colorizing::{ColoredText, TextStyle};
+pub use gradient::{ColorSpace, Direction, Gradient};
+pub use html::{ansi_to_html, html_stylesheet, spans_to_html, HtmlMode};
+pub use spaces::{Hsl, Hsv, Lab, Lch, Oklab, Oklch};
+pub use span::StyledText;
+pub use style::{Attributes, Style};
+pub use support::{background_from_env, background_from_vars, ColorChoice, ColorLevel, Stream};
+pub use theme::{Role, Theme};
\ No newline at end of file
@@ -0,0 +1,175 @@
+use crate::Color;
+use crate::conversion::ColorConversion;
+use crate::palette::display_rgb;
+use crate::spaces::{normalize_hue, Hsl};
+
+fn channel(c: f32) -> u8 {
+    (c.clamp(0.0, 255.0)).round() as u8
+}
+
+fn lerp(a: u8, b: u8, t: f32) -> u8 {
+    channel(a as f32 + (b as f32 - a as f32) * t)
+}
+
+// Keeps the alpha of the original so operations don't turn an `Rgba` opaque.
+fn with_alpha_of(color: Color, alpha: u8) -> Color {
+    match color {
+        Color::Rgb(r, g, b) if alpha < 255 => Color::Rgba(r, g, b, alpha),
+        color => color,
+    }
+}
+
+// Operations return `Rgb`, or `Rgba` when the input was translucent.
+// `Transparent` has no color to work with and is returned unchanged.
+impl Color {
+    pub fn alpha(&self) -> u8 {
+        match *self {
+            Color::Rgba(_, _, _, a) => a,
+            Color::Transparent => 0,
+            _ => self.to_rgba_u8().map(|(_, _, _, a)| a).unwrap_or(255),
+        }
+    }
+
+    pub fn with_alpha(&self, alpha: u8) -> Color {
+        match display_rgb(self) {
+            Some((r, g, b)) if alpha < 255 => Color::Rgba(r, g, b, alpha),
+            Some((r, g, b)) => Color::Rgb(r, g, b),
+            None => *self,
+        }
+    }
+
+    fn map_hsl(&self, f: impl Fn(Hsl) -> Hsl) -> Color {
+        match self.to_hsl() {
+            Some(hsl) => with_alpha_of(f(hsl).into(), self.alpha()),
+            None => *self,
+        }
+    }
+
+    // Raises HSL lightness by `amount` (0–1), as in Sass.
+    pub fn lighten(&self, amount: f32) -> Color {
+        self.map_hsl(|hsl| Hsl { l: (hsl.l + amount).clamp(0.0, 1.0), ..hsl })
+    }
+
+    pub fn darken(&self, amount: f32) -> Color {
+        self.lighten(-amount)
+    }
+
+    // Raises HSL saturation by `amount` (0–1); negative amounts desaturate.
+    pub fn saturate(&self, amount: f32) -> Color {
+        self.map_hsl(|hsl| Hsl { s: (hsl.s + amount).clamp(0.0, 1.0), ..hsl })
+    }
+
+    pub fn desaturate(&self, amount: f32) -> Color {
+        self.saturate(-amount)
+    }
+
+    pub fn rotate_hue(&self, degrees: f32) -> Color {
+        self.map_hsl(|hsl| Hsl { h: normalize_hue(hsl.h + degrees), ..hsl })
+    }
+
+    pub fn complement(&self) -> Color {
+        self.rotate_hue(180.0)
+    }
+
+    pub fn invert(&self) -> Color {
+        match display_rgb(self) {
+            Some((r, g, b)) => with_alpha_of(Color::Rgb(255 - r, 255 - g, 255 - b), self.alpha()),
+            None => *self,
+        }
+    }
+
+    // Linear interpolation in sRGB, alpha included: `t = 0` gives `self`, `t = 1` gives `other`.
+    pub fn mix(&self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+
+        match (display_rgb(self), display_rgb(&other)) {
+            (Some(a), Some(b)) => {
+                let alpha = lerp(self.alpha(), other.alpha(), t);
+                with_alpha_of(Color::Rgb(lerp(a.0, b.0, t), lerp(a.1, b.1, t), lerp(a.2, b.2, t)), alpha)
+            }
+            (Some(_), None) => *self,
+            _ => other,
+        }
+    }
+
+    // Composites a translucent color over an opaque `background`, giving what the eye sees.
+    pub fn over(&self, background: Color) -> Color {
+        let alpha = self.alpha();
+
+        match alpha {
+            255 => return *self,
+            0 => return background,
+            _ => {}
+        }
+
+        match (display_rgb(self), display_rgb(&background)) {
+            (Some(fg), Some(bg)) => {
+                let t = alpha as f32 / 255.0;
+                Color::Rgb(lerp(bg.0, fg.0, t), lerp(bg.1, fg.1, t), lerp(bg.2, fg.2, t))
+            }
+            _ => *self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mix_interpolates_channels_and_alpha() {
+        let black = Color::Rgb(0, 0, 0);
+        let white = Color::Rgb(255, 255, 255);
+
+        assert_eq!(black.mix(white, 0.0), black);
+        assert_eq!(black.mix(white, 1.0), white);
+        assert_eq!(black.mix(white, 0.5), Color::Rgb(128, 128, 128));
+        assert_eq!(black.mix(white, 2.0), white);
+        assert_eq!(Color::Rgba(255, 0, 0, 0).mix(Color::Rgb(255, 0, 0), 0.5), Color::Rgba(255, 0, 0, 128));
+        assert_eq!(black.mix(Color::Transparent, 0.5), black);
+    }
+
+    #[test]
+    fn over_composites_by_alpha() {
+        let white = Color::Rgb(255, 255, 255);
+
+        assert_eq!(Color::Rgba(0, 0, 0, 128).over(white), Color::Rgb(127, 127, 127));
+        assert_eq!(Color::Rgba(255, 0, 0, 255).over(white), Color::Rgba(255, 0, 0, 255));
+        assert_eq!(Color::Rgba(255, 0, 0, 0).over(white), white);
+        assert_eq!(Color::Rgb(1, 2, 3).over(white), Color::Rgb(1, 2, 3));
+        assert_eq!(Color::Transparent.over(white), white);
+    }
+
+    #[test]
+    fn lighten_and_darken_move_hsl_lightness() {
+        let base = Color::hsl(210.0, 0.5, 0.4);
+
+        assert_eq!(base.lighten(0.2), Color::hsl(210.0, 0.5, 0.6));
+        assert_eq!(base.darken(0.25), Color::Rgb(19, 38, 57));
+        assert_eq!(base.lighten(1.0), Color::Rgb(255, 255, 255));
+        assert_eq!(base.darken(1.0), Color::Rgb(0, 0, 0));
+        assert_eq!(Color::Rgba(51, 102, 153, 100).lighten(0.2).alpha(), 100);
+    }
+
+    #[test]
+    fn rotate_hue_wraps_around_the_wheel() {
+        let red = Color::Rgb(255, 0, 0);
+
+        assert_eq!(red.rotate_hue(120.0), Color::Rgb(0, 255, 0));
+        assert_eq!(red.rotate_hue(-120.0), Color::Rgb(0, 0, 255));
+        assert_eq!(red.rotate_hue(720.0), red);
+        assert_eq!(red.complement(), Color::Rgb(0, 255, 255));
+        assert_eq!(Color::Transparent.rotate_hue(90.0), Color::Transparent);
+    }
+
+    #[test]
+    fn alpha_is_kept_through_operations() {
+        let translucent = Color::Rgba(200, 100, 50, 64);
+
+        assert_eq!(translucent.alpha(), 64);
+        assert_eq!(translucent.invert(), Color::Rgba(55, 155, 205, 64));
+        assert_eq!(translucent.with_alpha(255), Color::Rgb(200, 100, 50));
+        assert_eq!(Color::Rgb(200, 100, 50).with_alpha(64), translucent);
+        assert_eq!(Color::Transparent.alpha(), 0);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::Color;
+use crate::conversion::ColorConversion;
+
+// The 16 basic colors in palette order, as SGR 30–37 and 90–97 address them.
+pub(crate) const BASIC: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+    Color::BrightBlack,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightWhite,
+];
+
+pub(crate) fn basic_index(color: Color) -> Option<u8> {
+    BASIC.iter().position(|basic| *basic == color).map(|i| i as u8)
+}
+
+fn palette_rgb(index: u8) -> (u8, u8, u8) {
+    let (r, g, b, _) = Color::indexed_to_rgba(index);
+
+    ((r * 255.0).round() as u8, (g * 255.0).round() as u8, (b * 255.0).round() as u8)
+}
+
+// The RGB value a terminal shows for `color`. `to_rgba` already looks indexed, gray and the
+// 16 basic colors up in the palette, so only the default color needs handling here.
+pub(crate) fn display_rgb(color: &Color) -> Option<(u8, u8, u8)> {
+    match *color {
+        Color::Transparent => None,
+        _ => color.to_rgba_u8().map(|(r, g, b, _)| (r, g, b)),
+    }
+}
+
+// The "redmean" weighted distance, a cheap approximation of how different two colors look.
+pub(crate) fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> i64 {
+    let mean = (a.0 as i64 + b.0 as i64) / 2;
+    let dr = a.0 as i64 - b.0 as i64;
+    let dg = a.1 as i64 - b.1 as i64;
+    let db = a.2 as i64 - b.2 as i64;
+
+    (((512 + mean) * dr * dr) >> 8) + 4 * dg * dg + (((767 - mean) * db * db) >> 8)
+}
+
+fn nearest(rgb: (u8, u8, u8), indices: impl Iterator<Item = u8>) -> u8 {
+    indices
+        .min_by_key(|index| distance(rgb, palette_rgb(*index)))
+        .unwrap_or(0)
+}
+
+// Skips 0–15, whose actual colors depend on the terminal's theme.
+pub(crate) fn nearest_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    nearest(rgb, 16..=255)
+}
+
+pub(crate) fn nearest_ansi16(rgb: (u8, u8, u8)) -> u8 {
+    nearest(rgb, 0..16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_colors_and_their_indices_show_the_same_rgb() {
+        for (index, color) in BASIC.iter().enumerate() {
+            assert_eq!(display_rgb(color), display_rgb(&Color::Indexed(index as u8)), "{}", color);
+        }
+
+        assert_eq!(display_rgb(&Color::Gray(0)), Some((8, 8, 8)));
+        assert_eq!(display_rgb(&Color::Transparent), None);
+    }
+
+    #[test]
+    fn nearest_ansi256_picks_cube_and_gray_entries() {
+        assert_eq!(nearest_ansi256((255, 0, 0)), 196);
+        assert_eq!(nearest_ansi256((95, 135, 175)), 67);
+        assert_eq!(nearest_ansi256((0, 0, 0)), 16);
+        assert_eq!(nearest_ansi256((128, 128, 128)), 244);
+        assert_eq!(nearest_ansi256((250, 5, 3)), 196);
+
+        for index in 16..=255 {
+            assert_eq!(nearest_ansi256(palette_rgb(index)), index);
+        }
+    }
+
+    #[test]
+    fn nearest_ansi16_stays_in_the_basic_range() {
+        assert_eq!(nearest_ansi16((250, 0, 0)), 9);
+        assert_eq!(nearest_ansi16((120, 0, 0)), 1);
+        assert_eq!(nearest_ansi16((10, 10, 10)), 0);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{Color, Role, Style, Theme};
+
+use serde::de::{Error, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::fmt::{Formatter, Result as FmtResult};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+// Reads any string the type's `FromStr` accepts, reporting its error as-is.
+struct ParseVisitor<T>(&'static str, PhantomData<T>);
+
+impl<T: FromStr<Err = String>> Visitor<'_> for ParseVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str(self.0)
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<T, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(ParseVisitor("a color name, hex code or CSS color function", PhantomData))
+    }
+}
+
+impl Serialize for Style {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_spec())
+    }
+}
+
+impl<'de> Deserialize<'de> for Style {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(ParseVisitor("a style such as \"bold red on black\"", PhantomData))
+    }
+}
+
+impl Serialize for Role {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Role {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(ParseVisitor("a theme role such as \"error\"", PhantomData))
+    }
+}
+
+// The same flat shape the TOML and JSON loaders read: `name`, then one style per role.
+impl Serialize for Theme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(Role::ALL.len() + 1))?;
+        map.serialize_entry("name", &self.name)?;
+
+        for role in Role::ALL {
+            map.serialize_entry(role.name(), &self.get(role))?;
+        }
+
+        map.end()
+    }
+}
+
+struct ThemeVisitor;
+
+impl<'de> Visitor<'de> for ThemeVisitor {
+    type Value = Theme;
+
+    fn expecting(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str("a map of theme roles to styles")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Theme, A::Error> {
+        let mut entries = Vec::new();
+
+        while let Some((key, value)) = access.next_entry::<String, String>()? {
+            entries.push((key, value));
+        }
+
+        Theme::from_entries(entries).map_err(A::Error::custom)
+    }
+}
+
+// Accepts `base` and partial maps, like `Theme::from_toml`.
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ThemeVisitor)
+    }
+}
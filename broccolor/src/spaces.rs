@@ -0,0 +1,391 @@
+use crate::Color;
+
+// Hue in degrees (0–360), saturation and lightness in 0–1.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Hsl {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+}
+
+// Hue in degrees (0–360), saturation and value in 0–1.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Hsv {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+}
+
+// CIE L*a*b* under D65, with L in 0–100.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Lab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+// Cylindrical CIE Lab: lightness, chroma and hue in degrees.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Lch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+}
+
+// Björn Ottosson's OKLab, with L in 0–1.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+// Cylindrical OKLab: lightness, chroma and hue in degrees.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Oklch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+}
+
+pub(crate) fn to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub(crate) fn from_linear(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn to_u8(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+pub(crate) fn normalize_hue(h: f32) -> f32 {
+    let h = h % 360.0;
+
+    if h < 0.0 {
+        h + 360.0
+    } else {
+        h
+    }
+}
+
+// Out-of-gamut values are clamped per channel.
+fn rgb_from_unit(r: f32, g: f32, b: f32) -> Color {
+    Color::Rgb(to_u8(r), to_u8(g), to_u8(b))
+}
+
+fn polar(a: f32, b: f32) -> (f32, f32) {
+    let c = (a * a + b * b).sqrt();
+    let h = if c < 1e-4 { 0.0 } else { normalize_hue(b.atan2(a).to_degrees()) };
+
+    (c, h)
+}
+
+fn cartesian(c: f32, h: f32) -> (f32, f32) {
+    let h = h.to_radians();
+
+    (c * h.cos(), c * h.sin())
+}
+
+impl Hsl {
+    pub fn from_rgb(r: f32, g: f32, b: f32) -> Self {
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let d = max - min;
+
+        if d == 0.0 {
+            return Self { h: 0.0, s: 0.0, l };
+        }
+
+        let s = d / (1.0 - (2.0 * l - 1.0).abs());
+
+        Self { h: hue(r, g, b, max, d), s, l }
+    }
+
+    pub fn to_rgb(&self) -> (f32, f32, f32) {
+        let s = self.s.clamp(0.0, 1.0);
+        let l = self.l.clamp(0.0, 1.0);
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+
+        from_chroma(normalize_hue(self.h), c, l - c / 2.0)
+    }
+}
+
+impl Hsv {
+    pub fn from_rgb(r: f32, g: f32, b: f32) -> Self {
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+
+        if d == 0.0 {
+            return Self { h: 0.0, s: 0.0, v: max };
+        }
+
+        Self { h: hue(r, g, b, max, d), s: d / max, v: max }
+    }
+
+    pub fn to_rgb(&self) -> (f32, f32, f32) {
+        let s = self.s.clamp(0.0, 1.0);
+        let v = self.v.clamp(0.0, 1.0);
+        let c = v * s;
+
+        from_chroma(normalize_hue(self.h), c, v - c)
+    }
+}
+
+fn hue(r: f32, g: f32, b: f32, max: f32, d: f32) -> f32 {
+    let h = if max == r {
+        ((g - b) / d) % 6.0
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+
+    normalize_hue(h * 60.0)
+}
+
+// Shared tail of the HSL and HSV conversions: chroma `c` at hue `h`, lifted by `m`.
+fn from_chroma(h: f32, c: f32, m: f32) -> (f32, f32, f32) {
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match (h / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    (r + m, g + m, b + m)
+}
+
+// D65 reference white
+const WHITE: (f32, f32, f32) = (0.95047, 1.0, 1.08883);
+const EPSILON: f32 = 216.0 / 24389.0;
+const KAPPA: f32 = 24389.0 / 27.0;
+
+impl Lab {
+    pub fn from_rgb(r: f32, g: f32, b: f32) -> Self {
+        let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+
+        let x = 0.4124564 * r + 0.3575761 * g + 0.1804375 * b;
+        let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+        let z = 0.0193339 * r + 0.119192 * g + 0.9503041 * b;
+
+        let f = |t: f32| if t > EPSILON { t.cbrt() } else { (KAPPA * t + 16.0) / 116.0 };
+        let (fx, fy, fz) = (f(x / WHITE.0), f(y / WHITE.1), f(z / WHITE.2));
+
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+
+    pub fn to_rgb(&self) -> (f32, f32, f32) {
+        let fy = (self.l + 16.0) / 116.0;
+        let fx = fy + self.a / 500.0;
+        let fz = fy - self.b / 200.0;
+
+        let f = |t: f32| if t.powi(3) > EPSILON { t.powi(3) } else { (116.0 * t - 16.0) / KAPPA };
+        let y = if self.l > KAPPA * EPSILON { fy.powi(3) } else { self.l / KAPPA };
+        let (x, y, z) = (f(fx) * WHITE.0, y * WHITE.1, f(fz) * WHITE.2);
+
+        let r = 3.2404542 * x - 1.5371385 * y - 0.4985314 * z;
+        let g = -0.969266 * x + 1.8760108 * y + 0.0415560 * z;
+        let b = 0.0556434 * x - 0.2040259 * y + 1.0572252 * z;
+
+        (from_linear(r), from_linear(g), from_linear(b))
+    }
+
+    pub fn to_lch(&self) -> Lch {
+        let (c, h) = polar(self.a, self.b);
+
+        Lch { l: self.l, c, h }
+    }
+}
+
+impl Lch {
+    pub fn to_lab(&self) -> Lab {
+        let (a, b) = cartesian(self.c, self.h);
+
+        Lab { l: self.l, a, b }
+    }
+}
+
+impl Oklab {
+    pub fn from_rgb(r: f32, g: f32, b: f32) -> Self {
+        let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+
+        let l = (0.41222146 * r + 0.53633255 * g + 0.051445995 * b).cbrt();
+        let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+        let s = (0.08830246 * r + 0.28171885 * g + 0.6299787 * b).cbrt();
+
+        Self {
+            l: 0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            a: 1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            b: 0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+        }
+    }
+
+    pub fn to_rgb(&self) -> (f32, f32, f32) {
+        let l = (self.l + 0.39633778 * self.a + 0.21580376 * self.b).powi(3);
+        let m = (self.l - 0.105561346 * self.a - 0.06385417 * self.b).powi(3);
+        let s = (self.l - 0.08948418 * self.a - 1.2914855 * self.b).powi(3);
+
+        let r = 4.0767417 * l - 3.3077116 * m + 0.23096994 * s;
+        let g = -1.268438 * l + 2.6097574 * m - 0.34131938 * s;
+        let b = -0.0041960863 * l - 0.7034186 * m + 1.7076147 * s;
+
+        (from_linear(r), from_linear(g), from_linear(b))
+    }
+
+    pub fn to_oklch(&self) -> Oklch {
+        let (c, h) = polar(self.a, self.b);
+
+        Oklch { l: self.l, c, h }
+    }
+}
+
+impl Oklch {
+    pub fn to_oklab(&self) -> Oklab {
+        let (a, b) = cartesian(self.c, self.h);
+
+        Oklab { l: self.l, a, b }
+    }
+}
+
+impl From<Hsl> for Color {
+    fn from(hsl: Hsl) -> Self {
+        let (r, g, b) = hsl.to_rgb();
+        rgb_from_unit(r, g, b)
+    }
+}
+
+impl From<Hsv> for Color {
+    fn from(hsv: Hsv) -> Self {
+        let (r, g, b) = hsv.to_rgb();
+        rgb_from_unit(r, g, b)
+    }
+}
+
+impl From<Lab> for Color {
+    fn from(lab: Lab) -> Self {
+        let (r, g, b) = lab.to_rgb();
+        rgb_from_unit(r, g, b)
+    }
+}
+
+impl From<Lch> for Color {
+    fn from(lch: Lch) -> Self {
+        Color::from(lch.to_lab())
+    }
+}
+
+impl From<Oklab> for Color {
+    fn from(oklab: Oklab) -> Self {
+        let (r, g, b) = oklab.to_rgb();
+        rgb_from_unit(r, g, b)
+    }
+}
+
+impl From<Oklch> for Color {
+    fn from(oklch: Oklch) -> Self {
+        Color::from(oklch.to_oklab())
+    }
+}
+
+impl Color {
+    pub fn hsl(h: f32, s: f32, l: f32) -> Self {
+        Hsl { h, s, l }.into()
+    }
+
+    pub fn hsv(h: f32, s: f32, v: f32) -> Self {
+        Hsv { h, s, v }.into()
+    }
+
+    pub fn lab(l: f32, a: f32, b: f32) -> Self {
+        Lab { l, a, b }.into()
+    }
+
+    pub fn lch(l: f32, c: f32, h: f32) -> Self {
+        Lch { l, c, h }.into()
+    }
+
+    pub fn oklab(l: f32, a: f32, b: f32) -> Self {
+        Oklab { l, a, b }.into()
+    }
+
+    pub fn oklch(l: f32, c: f32, h: f32) -> Self {
+        Oklch { l, c, h }.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::ColorConversion;
+
+    fn close(actual: f32, expected: f32, tolerance: f32) -> bool {
+        (actual - expected).abs() <= tolerance
+    }
+
+    #[test]
+    fn hsl_and_hsv_match_reference_values() {
+        let orange = Color::Rgb(255, 136, 0);
+        let hsl = orange.to_hsl().unwrap();
+        let hsv = orange.to_hsv().unwrap();
+
+        assert!(close(hsl.h, 32.0, 0.01) && close(hsl.s, 1.0, 0.001) && close(hsl.l, 0.5, 0.001), "{:?}", hsl);
+        assert!(close(hsv.h, 32.0, 0.01) && close(hsv.s, 1.0, 0.001) && close(hsv.v, 1.0, 0.001), "{:?}", hsv);
+        assert_eq!(Color::hsl(32.0, 1.0, 0.5), orange);
+        assert_eq!(Color::hsv(32.0, 1.0, 1.0), orange);
+        assert_eq!(Color::hsl(210.0, 0.5, 0.4), Color::Rgb(51, 102, 153));
+    }
+
+    #[test]
+    fn lab_and_lch_match_reference_values() {
+        let lab = Color::Rgb(255, 0, 0).to_lab().unwrap();
+        let lch = Color::Rgb(255, 0, 0).to_lch().unwrap();
+        let blue = Color::Rgb(0, 0, 255).to_lab().unwrap();
+
+        assert!(close(lab.l, 53.24, 0.05) && close(lab.a, 80.09, 0.05) && close(lab.b, 67.20, 0.05), "{:?}", lab);
+        assert!(close(lch.c, 104.55, 0.05) && close(lch.h, 40.0, 0.05), "{:?}", lch);
+        assert!(close(blue.l, 32.30, 0.05) && close(blue.a, 79.19, 0.05) && close(blue.b, -107.86, 0.05), "{:?}", blue);
+        assert_eq!(Color::lab(53.24, 80.09, 67.20), Color::Rgb(255, 0, 0));
+    }
+
+    #[test]
+    fn oklab_and_oklch_match_reference_values() {
+        let oklab = Color::Rgb(255, 0, 0).to_oklab().unwrap();
+        let oklch = Color::Rgb(0, 0, 255).to_oklch().unwrap();
+
+        assert!(close(oklab.l, 0.62796, 0.001) && close(oklab.a, 0.22486, 0.001) && close(oklab.b, 0.12585, 0.001), "{:?}", oklab);
+        assert!(close(oklch.l, 0.45201, 0.001) && close(oklch.c, 0.31321, 0.001) && close(oklch.h, 264.05, 0.05), "{:?}", oklch);
+        assert_eq!(Color::oklch(0.45201, 0.31321, 264.05), Color::Rgb(0, 0, 255));
+    }
+
+    #[test]
+    fn every_space_round_trips_through_rgb() {
+        for color in [Color::Rgb(12, 200, 99), Color::Rgb(255, 255, 255), Color::Rgb(0, 0, 0), Color::Rgb(128, 64, 200)] {
+            assert_eq!(Color::from(color.to_hsl().unwrap()), color);
+            assert_eq!(Color::from(color.to_hsv().unwrap()), color);
+            assert_eq!(Color::from(color.to_lab().unwrap()), color);
+            assert_eq!(Color::from(color.to_lch().unwrap()), color);
+            assert_eq!(Color::from(color.to_oklab().unwrap()), color);
+            assert_eq!(Color::from(color.to_oklch().unwrap()), color);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{ColorLevel, Style};
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// Text split into runs that each carry their full effective style.
+// Styles pushed onto the stack layer over the enclosing ones, and popping
+// one goes back to exactly what was active before it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StyledText {
+    spans: Vec<(Style, String)>,
+    stack: Vec<Style>,
+}
+
+impl StyledText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_spans(spans: Vec<(Style, String)>) -> Self {
+        Self {
+            spans,
+            stack: Vec::new(),
+        }
+    }
+
+    // The style new text gets: every pushed style layered in order.
+    pub fn current(&self) -> Style {
+        self.stack.last().copied().unwrap_or_default()
+    }
+
+    pub fn push(mut self, style: Style) -> Self {
+        let style = self.current().patch(style);
+        self.stack.push(style);
+        self
+    }
+
+    pub fn pop(mut self) -> Self {
+        self.stack.pop();
+        self
+    }
+
+    pub fn text(mut self, text: &str) -> Self {
+        if text.is_empty() {
+            return self;
+        }
+
+        let style = self.current();
+
+        match self.spans.last_mut() {
+            Some((last, content)) if *last == style => content.push_str(text),
+            _ => self.spans.push((style, text.to_string())),
+        }
+
+        self
+    }
+
+    pub fn styled(self, style: Style, text: &str) -> Self {
+        self.push(style).text(text).pop()
+    }
+
+    // Appends `other` as a nested span, its styles layered over the current one.
+    pub fn append(mut self, other: StyledText) -> Self {
+        let base = self.current();
+
+        for (style, text) in other.spans {
+            self.stack.push(base.patch(style));
+            self = self.text(&text);
+            self.stack.pop();
+        }
+
+        self
+    }
+
+    pub fn spans(&self) -> &[(Style, String)] {
+        &self.spans
+    }
+
+    pub fn into_spans(self) -> Vec<(Style, String)> {
+        self.spans
+    }
+
+    pub fn plain(&self) -> String {
+        self.spans.iter().map(|(_, text)| text.as_str()).collect()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_for(ColorLevel::TrueColor)
+    }
+
+    pub fn render_for(&self, level: ColorLevel) -> String {
+        if level == ColorLevel::None {
+            return self.plain();
+        }
+
+        let mut out = String::new();
+        let mut active = Style::new();
+
+        for (style, text) in &self.spans {
+            if *style != active {
+                out.push_str(&active.suffix());
+                out.push_str(&style.prefix_for(level));
+                active = *style;
+            }
+
+            out.push_str(text);
+        }
+
+        out.push_str(&active.suffix());
+        out
+    }
+}
+
+impl Display for StyledText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.render())
+    }
+}
+
+// True when the parameters of an SGR sequence switch off anything an enclosing style may have set.
+fn clears_style(params: &str) -> bool {
+    let mut params = params.split(';');
+
+    while let Some(param) = params.next() {
+        match param {
+            "" | "0" | "22" | "23" | "24" | "25" | "27" | "28" | "29" | "39" | "49" | "55" | "59" => return true,
+            "4:0" => return true,
+            // Extended colors carry their own sub-parameters, which aren't codes
+            "38" | "48" | "58" => match params.next() {
+                Some("5") => {
+                    params.next();
+                }
+                Some("2") => {
+                    params.nth(2);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    false
+}
+
+// Re-emits `prefix` after every sequence inside `content` that would cancel it,
+// so text following a nested span keeps the enclosing style.
+pub(crate) fn restore_after_resets(content: &str, prefix: &str) -> String {
+    if prefix.is_empty() || !content.contains('\x1b') {
+        return content.to_string();
+    }
+
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("\x1b[") {
+        out.push_str(&rest[..start]);
+        let sequence = &rest[start..];
+
+        let end = sequence[2..]
+            .find(|c: char| !(c.is_ascii_digit() || c == ';' || c == ':'))
+            .map(|i| i + 2);
+
+        match end {
+            Some(end) if sequence[end..].starts_with('m') => {
+                out.push_str(&sequence[..=end]);
+
+                if clears_style(&sequence[2..end]) {
+                    out.push_str(prefix);
+                }
+
+                rest = &sequence[end + 1..];
+            }
+            _ => {
+                out.push_str("\x1b[");
+                rest = &sequence[2..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn clears_style_spots_resets_but_not_color_arguments() {
+        assert!(clears_style(""));
+        assert!(clears_style("0"));
+        assert!(clears_style("1;22"));
+        assert!(clears_style("4:0"));
+        assert!(clears_style("38;5;196;39"));
+        assert!(!clears_style("1;31"));
+        assert!(!clears_style("38;5;22"));
+        assert!(!clears_style("48;2;0;22;255"));
+    }
+
+    #[test]
+    fn restore_after_resets_reapplies_the_outer_prefix() {
+        let inner = Style::new().bold().paint("inner");
+        let restored = restore_after_resets(&format!("a {} b\x1b[0m c", inner), "\x1b[31m");
+
+        assert_eq!(restored, "a \x1b[1minner\x1b[22m\x1b[31m b\x1b[0m\x1b[31m c");
+        assert_eq!(restore_after_resets("\x1b[38;5;22mgreen", "\x1b[31m"), "\x1b[38;5;22mgreen");
+    }
+
+    #[test]
+    fn nested_spans_keep_the_enclosing_style() {
+        let inner = Style::new().fg(Color::Blue).paint("inner");
+        let outer = Style::new().fg(Color::Red).dim().paint(format!("a {} b", inner));
+
+        assert_eq!(outer, "\x1b[2;31ma \x1b[34minner\x1b[39m\x1b[2;31m b\x1b[22;39m");
+    }
+
+    #[test]
+    fn popping_restores_the_previous_style() {
+        let text = StyledText::new()
+            .push(Style::new().bold())
+            .text("a")
+            .styled(Style::new().fg(Color::Red), "b")
+            .text("c")
+            .pop()
+            .text("d");
+
+        assert_eq!(text.spans(), [
+            (Style::new().bold(), "a".to_string()),
+            (Style::new().bold().fg(Color::Red), "b".to_string()),
+            (Style::new().bold(), "c".to_string()),
+            (Style::new(), "d".to_string()),
+        ]);
+        assert_eq!(text.render_for(ColorLevel::None), "abcd");
+    }
+}
@@ -0,0 +1,625 @@
+use crate::{Color, ColorLevel};
+use crate::conversion::ColorConversion;
+use crate::span::restore_after_resets;
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::{BitOr, BitOrAssign};
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Attributes(u16);
+
+impl Attributes {
+    pub const BOLD: Attributes = Attributes(1 << 0);
+    pub const DIM: Attributes = Attributes(1 << 1);
+    pub const ITALIC: Attributes = Attributes(1 << 2);
+    pub const UNDERLINE: Attributes = Attributes(1 << 3);
+    pub const DOUBLE_UNDERLINE: Attributes = Attributes(1 << 4);
+    pub const CURLY_UNDERLINE: Attributes = Attributes(1 << 5);
+    pub const DOTTED_UNDERLINE: Attributes = Attributes(1 << 6);
+    pub const DASHED_UNDERLINE: Attributes = Attributes(1 << 7);
+    pub const BLINK: Attributes = Attributes(1 << 8);
+    pub const RAPID_BLINK: Attributes = Attributes(1 << 9);
+    pub const REVERSE: Attributes = Attributes(1 << 10);
+    pub const HIDDEN: Attributes = Attributes(1 << 11);
+    pub const STRIKETHROUGH: Attributes = Attributes(1 << 12);
+    pub const OVERLINE: Attributes = Attributes(1 << 13);
+
+    // A cell has one underline style, so setting one replaces the others.
+    pub const UNDERLINES: Attributes = Attributes(
+        Self::UNDERLINE.0
+            | Self::DOUBLE_UNDERLINE.0
+            | Self::CURLY_UNDERLINE.0
+            | Self::DOTTED_UNDERLINE.0
+            | Self::DASHED_UNDERLINE.0,
+    );
+
+    // (attribute, SGR code that sets it, SGR code that clears it)
+    const CODES: [(Attributes, &'static str, &'static str); 14] = [
+        (Attributes::BOLD, "1", "22"),
+        (Attributes::DIM, "2", "22"),
+        (Attributes::ITALIC, "3", "23"),
+        (Attributes::UNDERLINE, "4", "24"),
+        (Attributes::DOUBLE_UNDERLINE, "21", "24"),
+        (Attributes::CURLY_UNDERLINE, "4:3", "24"),
+        (Attributes::DOTTED_UNDERLINE, "4:4", "24"),
+        (Attributes::DASHED_UNDERLINE, "4:5", "24"),
+        (Attributes::BLINK, "5", "25"),
+        (Attributes::RAPID_BLINK, "6", "25"),
+        (Attributes::REVERSE, "7", "27"),
+        (Attributes::HIDDEN, "8", "28"),
+        (Attributes::STRIKETHROUGH, "9", "29"),
+        (Attributes::OVERLINE, "53", "55"),
+    ];
+
+    // The names `FromStr` reads back, in the same order as `CODES`.
+    const NAMES: [(Attributes, &'static str); 14] = [
+        (Attributes::BOLD, "bold"),
+        (Attributes::DIM, "dim"),
+        (Attributes::ITALIC, "italic"),
+        (Attributes::UNDERLINE, "underline"),
+        (Attributes::DOUBLE_UNDERLINE, "double-underline"),
+        (Attributes::CURLY_UNDERLINE, "curly-underline"),
+        (Attributes::DOTTED_UNDERLINE, "dotted-underline"),
+        (Attributes::DASHED_UNDERLINE, "dashed-underline"),
+        (Attributes::BLINK, "blink"),
+        (Attributes::RAPID_BLINK, "rapid-blink"),
+        (Attributes::REVERSE, "reverse"),
+        (Attributes::HIDDEN, "hidden"),
+        (Attributes::STRIKETHROUGH, "strikethrough"),
+        (Attributes::OVERLINE, "overline"),
+    ];
+
+    pub const fn empty() -> Self {
+        Attributes(0)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, other: Attributes) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn intersects(&self, other: Attributes) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub const fn union(self, other: Attributes) -> Self {
+        Attributes(self.0 | other.0)
+    }
+
+    pub const fn difference(self, other: Attributes) -> Self {
+        Attributes(self.0 & !other.0)
+    }
+
+    pub fn insert(&mut self, other: Attributes) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Attributes) {
+        self.0 &= !other.0;
+    }
+
+    pub(crate) fn sgr_params(&self) -> Vec<&'static str> {
+        Self::CODES
+            .iter()
+            .filter(|(attribute, _, _)| self.contains(*attribute))
+            .map(|(_, on, _)| *on)
+            .collect()
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMES
+            .iter()
+            .filter(|(attribute, _)| self.contains(*attribute))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+
+    // The codes that clear exactly these attributes, without touching any others.
+    pub(crate) fn reset_params(&self) -> Vec<&'static str> {
+        let mut params: Vec<&'static str> = Vec::new();
+
+        for (attribute, _, off) in Self::CODES.iter() {
+            if self.contains(*attribute) && !params.contains(off) {
+                params.push(off);
+            }
+        }
+
+        params
+    }
+}
+
+impl FromStr for Attributes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace('_', "-");
+
+        let name = match name.as_str() {
+            "faint" => "dim",
+            "underlined" => "underline",
+            "undercurl" => "curly-underline",
+            "inverse" => "reverse",
+            "conceal" => "hidden",
+            "strike" => "strikethrough",
+            name => name,
+        };
+
+        Self::NAMES
+            .iter()
+            .find(|(_, candidate)| *candidate == name)
+            .map(|(attribute, _)| *attribute)
+            .ok_or_else(|| format!("Unknown attribute: '{}'", s))
+    }
+}
+
+impl BitOr for Attributes {
+    type Output = Attributes;
+
+    fn bitor(self, other: Attributes) -> Attributes {
+        self.union(other)
+    }
+}
+
+impl BitOrAssign for Attributes {
+    fn bitor_assign(&mut self, other: Attributes) {
+        self.insert(other);
+    }
+}
+
+// Foreground, background, underline color and attributes that render as one escape sequence.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub underline_color: Option<Color>,
+    pub attrs: Attributes,
+}
+
+// The parameters of a `\x1b[...m` sequence, e.g. `38;5;208`.
+fn sgr_params(code: &str) -> &str {
+    code.trim_start_matches("\x1b[").trim_end_matches('m')
+}
+
+impl Style {
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            underline_color: None,
+            attrs: Attributes::empty(),
+        }
+    }
+
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    pub const fn underline_color(mut self, color: Color) -> Self {
+        self.underline_color = Some(color);
+        self
+    }
+
+    pub const fn attrs(mut self, attrs: Attributes) -> Self {
+        if attrs.intersects(Attributes::UNDERLINES) {
+            self.attrs = self.attrs.difference(Attributes::UNDERLINES);
+        }
+
+        self.attrs = self.attrs.union(attrs);
+        self
+    }
+
+    pub const fn bold(self) -> Self {
+        self.attrs(Attributes::BOLD)
+    }
+
+    pub const fn dim(self) -> Self {
+        self.attrs(Attributes::DIM)
+    }
+
+    pub const fn italic(self) -> Self {
+        self.attrs(Attributes::ITALIC)
+    }
+
+    pub const fn underline(self) -> Self {
+        self.attrs(Attributes::UNDERLINE)
+    }
+
+    pub const fn double_underline(self) -> Self {
+        self.attrs(Attributes::DOUBLE_UNDERLINE)
+    }
+
+    pub const fn curly_underline(self) -> Self {
+        self.attrs(Attributes::CURLY_UNDERLINE)
+    }
+
+    pub const fn dotted_underline(self) -> Self {
+        self.attrs(Attributes::DOTTED_UNDERLINE)
+    }
+
+    pub const fn dashed_underline(self) -> Self {
+        self.attrs(Attributes::DASHED_UNDERLINE)
+    }
+
+    pub const fn blink(self) -> Self {
+        self.attrs(Attributes::BLINK)
+    }
+
+    pub const fn rapid_blink(self) -> Self {
+        self.attrs(Attributes::RAPID_BLINK)
+    }
+
+    pub const fn reverse(self) -> Self {
+        self.attrs(Attributes::REVERSE)
+    }
+
+    pub const fn hidden(self) -> Self {
+        self.attrs(Attributes::HIDDEN)
+    }
+
+    pub const fn strikethrough(self) -> Self {
+        self.attrs(Attributes::STRIKETHROUGH)
+    }
+
+    pub const fn overline(self) -> Self {
+        self.attrs(Attributes::OVERLINE)
+    }
+
+    pub const fn remove(mut self, attrs: Attributes) -> Self {
+        self.attrs = self.attrs.difference(attrs);
+        self
+    }
+
+    // Blends every translucent color of the style over `background`, the color behind the text.
+    pub fn composite(self, background: Color) -> Self {
+        // `Transparent` stands for the terminal default rather than a see-through color
+        let blend = |color: Color, behind: Color| match color {
+            Color::Transparent => color,
+            color => color.over(behind),
+        };
+
+        let bg = self.bg.map(|bg| blend(bg, background));
+        let behind = bg.unwrap_or(background);
+
+        Self {
+            fg: self.fg.map(|fg| blend(fg, behind)),
+            bg,
+            underline_color: self.underline_color.map(|color| blend(color, behind)),
+            attrs: self.attrs,
+        }
+    }
+
+    pub fn is_plain(&self) -> bool {
+        self.fg.is_none() && self.bg.is_none() && self.underline_color.is_none() && self.attrs.is_empty()
+    }
+
+    // The style as a spec that `FromStr` reads back, e.g. `bold red on black`, or `none` when plain.
+    pub fn to_spec(&self) -> String {
+        let mut words: Vec<String> = self.attrs.names().iter().map(|name| name.to_string()).collect();
+
+        if let Some(fg) = self.fg {
+            words.push(fg.to_string());
+        }
+
+        if let Some(bg) = self.bg {
+            words.push(format!("on {}", bg));
+        }
+
+        if let Some(color) = self.underline_color {
+            words.push(format!("underline-color {}", color));
+        }
+
+        if words.is_empty() {
+            return "none".to_string();
+        }
+
+        words.join(" ")
+    }
+
+    // Layers `other` on top: its colors win where set and the attributes combine.
+    pub fn patch(self, other: Style) -> Self {
+        let base = Self {
+            fg: other.fg.or(self.fg),
+            bg: other.bg.or(self.bg),
+            underline_color: other.underline_color.or(self.underline_color),
+            attrs: self.attrs,
+        };
+
+        base.attrs(other.attrs)
+    }
+
+    // One SGR sequence setting every part of the style, or nothing for a plain style.
+    pub fn prefix(&self) -> String {
+        self.prefix_for(ColorLevel::TrueColor)
+    }
+
+    // Like `prefix`, with colors downsampled to what the terminal supports.
+    pub fn prefix_for(&self, level: ColorLevel) -> String {
+        if self.is_plain() || level == ColorLevel::None {
+            return String::new();
+        }
+
+        let mut params: Vec<String> = self.attrs.sgr_params().into_iter().map(String::from).collect();
+
+        // Translucent colors are blended over the style's own background when it has one
+        let style = match self.bg {
+            Some(bg) if bg.alpha() == 255 => self.composite(bg),
+            _ => *self,
+        };
+
+        let codes = [
+            style.fg.map(|color| color.to_ansi_code_for(level)),
+            style.bg.map(|color| color.to_background_ansi_code_for(level)),
+            style.underline_color.map(|color| color.to_underline_ansi_code_for(level)),
+        ];
+
+        for code in codes.iter().flatten().filter(|code| !code.is_empty()) {
+            params.push(sgr_params(code).to_string());
+        }
+
+        if params.is_empty() {
+            return String::new();
+        }
+
+        format!("\x1b[{}m", params.join(";"))
+    }
+
+    // Clears only what this style set, so styling applied outside the span survives.
+    pub fn suffix(&self) -> String {
+        if self.is_plain() {
+            return String::new();
+        }
+
+        let mut params = self.attrs.reset_params();
+
+        if self.fg.is_some() {
+            params.push("39");
+        }
+
+        if self.bg.is_some() {
+            params.push("49");
+        }
+
+        if self.underline_color.is_some() {
+            params.push("59");
+        }
+
+        format!("\x1b[{}m", params.join(";"))
+    }
+
+    // Styles `content`, re-applying this style after any nested span inside it ends.
+    pub fn paint<T: Display>(&self, content: T) -> String {
+        self.paint_for(content, ColorLevel::TrueColor)
+    }
+
+    pub fn paint_for<T: Display>(&self, content: T, level: ColorLevel) -> String {
+        let content = content.to_string();
+        let prefix = self.prefix_for(level);
+
+        if prefix.is_empty() {
+            return content;
+        }
+
+        let content = restore_after_resets(&content, &prefix);
+        let content = content.strip_suffix(prefix.as_str()).unwrap_or(&content);
+
+        format!("{}{}{}", prefix, content, self.suffix())
+    }
+}
+
+impl From<Color> for Style {
+    fn from(color: Color) -> Self {
+        Style::new().fg(color)
+    }
+}
+
+impl From<Attributes> for Style {
+    fn from(attrs: Attributes) -> Self {
+        Style::new().attrs(attrs)
+    }
+}
+
+impl Display for Style {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.prefix())
+    }
+}
+
+// Takes the longest run of up to two words that names a color, e.g. `bright red`.
+fn take_color(words: &[&str]) -> Option<(Color, usize)> {
+    (1..=words.len().min(2))
+        .rev()
+        .find_map(|n| words[..n].join(" ").parse::<Color>().ok().map(|color| (color, n)))
+}
+
+// Splits on whitespace, except inside parentheses so `rgb(255 136 0)` stays one word.
+fn words(s: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut depth = 0usize;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(begin) = start.take() {
+                    words.push(&s[begin..i]);
+                }
+
+                continue;
+            }
+            _ => {}
+        }
+
+        start.get_or_insert(i);
+    }
+
+    if let Some(begin) = start {
+        words.push(&s[begin..]);
+    }
+
+    words
+}
+
+// Parses specs like `bold red on black` or `italic #FF8800 underline`: attributes and a foreground
+// in any order, with `on <color>` for the background and `underline-color <color>`.
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = words(s);
+        let mut style = Style::new();
+        let mut i = 0;
+
+        while i < words.len() {
+            let word = words[i];
+
+            if word.eq_ignore_ascii_case("on") {
+                let (color, taken) = take_color(&words[i + 1..])
+                    .ok_or_else(|| format!("Expected a background color after 'on' in '{}'", s))?;
+
+                style = style.bg(color);
+                i += 1 + taken;
+            } else if word.eq_ignore_ascii_case("underline-color") {
+                let (color, taken) = take_color(&words[i + 1..])
+                    .ok_or_else(|| format!("Expected a color after 'underline-color' in '{}'", s))?;
+
+                style = style.underline_color(color);
+                i += 1 + taken;
+            } else if matches!(word.to_lowercase().as_str(), "none" | "plain" | "default") {
+                i += 1;
+            } else if let Ok(attrs) = word.parse::<Attributes>() {
+                style = style.attrs(attrs);
+                i += 1;
+            } else if let Some((color, taken)) = take_color(&words[i..]) {
+                style = style.fg(color);
+                i += taken;
+            } else if word.starts_with('#') || word.contains('(') {
+                // Clearly meant as a color, so the color parser's error says more
+                return Err(word.parse::<Color>().err().unwrap_or_default());
+            } else {
+                return Err(format!("Unknown style: '{}' in '{}'", word, s));
+            }
+        }
+
+        Ok(style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_attribute_has_its_own_on_and_off_code() {
+        let expected = [
+            (Style::new().bold(), "1", "22"),
+            (Style::new().dim(), "2", "22"),
+            (Style::new().italic(), "3", "23"),
+            (Style::new().underline(), "4", "24"),
+            (Style::new().double_underline(), "21", "24"),
+            (Style::new().curly_underline(), "4:3", "24"),
+            (Style::new().dotted_underline(), "4:4", "24"),
+            (Style::new().dashed_underline(), "4:5", "24"),
+            (Style::new().blink(), "5", "25"),
+            (Style::new().rapid_blink(), "6", "25"),
+            (Style::new().reverse(), "7", "27"),
+            (Style::new().hidden(), "8", "28"),
+            (Style::new().strikethrough(), "9", "29"),
+            (Style::new().overline(), "53", "55"),
+        ];
+
+        for (style, on, off) in expected {
+            assert_eq!(style.prefix(), format!("\x1b[{}m", on), "{}", style.to_spec());
+            assert_eq!(style.suffix(), format!("\x1b[{}m", off), "{}", style.to_spec());
+        }
+    }
+
+    #[test]
+    fn off_codes_clear_only_the_attributes_that_share_them() {
+        for (a, on_a, off_a) in Attributes::CODES {
+            for (b, on_b, off_b) in Attributes::CODES {
+                // Underline styles replace each other, so they never stack
+                if a == b || (a.intersects(Attributes::UNDERLINES) && b.intersects(Attributes::UNDERLINES)) {
+                    continue;
+                }
+
+                let spans = crate::parse_ansi(&format!("\x1b[{};{}m\x1b[{}mx", on_a, on_b, off_a));
+                let expected = if off_a == off_b { Attributes::empty() } else { b };
+
+                assert_eq!(spans[0].0.attrs, expected, "{} then {}", on_b, off_a);
+            }
+        }
+    }
+
+    #[test]
+    fn reset_params_name_each_shared_code_once() {
+        assert_eq!((Attributes::BOLD | Attributes::DIM).reset_params(), ["22"]);
+        assert_eq!((Attributes::BOLD | Attributes::ITALIC).reset_params(), ["22", "23"]);
+        assert_eq!((Attributes::BLINK | Attributes::RAPID_BLINK | Attributes::OVERLINE).reset_params(), ["25", "55"]);
+        assert_eq!(Attributes::empty().reset_params(), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn suffix_clears_only_what_the_style_set() {
+        assert_eq!(Style::new().suffix(), "");
+        assert_eq!(Style::new().fg(Color::Red).suffix(), "\x1b[39m");
+        assert_eq!(Style::new().bold().dim().bg(Color::Blue).suffix(), "\x1b[22;49m");
+        assert_eq!(Style::new().underline_color(Color::Red).italic().suffix(), "\x1b[23;59m");
+    }
+
+    #[test]
+    fn prefix_combines_attributes_and_colors() {
+        let style = Style::new().bold().fg(Color::Red).bg(Color::Rgb(0, 0, 128));
+
+        assert_eq!(style.prefix(), "\x1b[1;31;48;2;0;0;128m");
+        assert_eq!(style.prefix_for(ColorLevel::Ansi16), "\x1b[1;31;44m");
+        assert_eq!(style.prefix_for(ColorLevel::None), "");
+        assert_eq!(Style::new().prefix(), "");
+    }
+
+    #[test]
+    fn patch_layers_colors_and_combines_attributes() {
+        let base = Style::new().fg(Color::Red).bg(Color::Black).bold().underline();
+        let patched = base.patch(Style::new().fg(Color::Green).curly_underline());
+
+        assert_eq!(patched.fg, Some(Color::Green));
+        assert_eq!(patched.bg, Some(Color::Black));
+        assert_eq!(patched.attrs, Attributes::BOLD | Attributes::CURLY_UNDERLINE);
+        assert_eq!(base.patch(Style::new()), base);
+    }
+
+    #[test]
+    fn parses_specs() {
+        let style: Style = "bold bright red on black underline-color #ff8800".parse().unwrap();
+
+        assert_eq!(style, Style::new().bold().fg(Color::BrightRed).bg(Color::Black).underline_color(Color::Rgb(255, 136, 0)));
+        assert_eq!("none".parse::<Style>(), Ok(Style::new()));
+        assert_eq!("faint inverse".parse::<Style>(), Ok(Style::new().dim().reverse()));
+        assert!("bold on".parse::<Style>().unwrap_err().contains("background color"));
+        assert!("sparkly".parse::<Style>().unwrap_err().contains("'sparkly'"));
+    }
+
+    #[test]
+    fn specs_round_trip_through_sgr() {
+        for spec in ["bold red on black", "italic dashed-underline rgb(1 2 3)", "dim overline on bright blue", "none"] {
+            let style: Style = spec.parse().unwrap();
+
+            assert_eq!(style.to_spec().parse::<Style>(), Ok(style), "{}", spec);
+        }
+
+        let style: Style = "bold red on black".parse().unwrap();
+        assert_eq!(style.prefix(), "\x1b[1;31;40m");
+        assert_eq!(crate::parse_ansi(&style.paint("x")), [(style, "x".to_string())]);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::Color;
+use crate::palette::BASIC;
+
+use std::io::IsTerminal;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorLevel {
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+// The value of a `--color=auto|always|never` option.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+// 0 means no override, otherwise the level plus one.
+static OVERRIDE: AtomicU8 = AtomicU8::new(0);
+static STDOUT_LEVEL: OnceLock<ColorLevel> = OnceLock::new();
+static STDERR_LEVEL: OnceLock<ColorLevel> = OnceLock::new();
+
+impl ColorLevel {
+    // The level for `stream`, detected once and cached, unless overridden.
+    pub fn detect(stream: Stream) -> ColorLevel {
+        if let Some(level) = Self::get_override() {
+            return level;
+        }
+
+        let cache = match stream {
+            Stream::Stdout => &STDOUT_LEVEL,
+            Stream::Stderr => &STDERR_LEVEL,
+        };
+
+        *cache.get_or_init(|| {
+            let is_tty = match stream {
+                Stream::Stdout => std::io::stdout().is_terminal(),
+                Stream::Stderr => std::io::stderr().is_terminal(),
+            };
+
+            Self::from_env(is_tty)
+        })
+    }
+
+    // Uncached detection from the environment, given whether the stream is a terminal.
+    pub fn from_env(is_tty: bool) -> ColorLevel {
+        Self::from_vars(|name| std::env::var(name).ok(), is_tty)
+    }
+
+    // Detection against any variable lookup, so it can be exercised without touching the environment.
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>, is_tty: bool) -> ColorLevel {
+        let set = |name: &str| var(name).is_some_and(|value| !value.is_empty());
+        let enabled = |name: &str| var(name).is_some_and(|value| !value.is_empty() && value != "0");
+
+        if set("NO_COLOR") {
+            return ColorLevel::None;
+        }
+
+        let forced = enabled("CLICOLOR_FORCE") || enabled("FORCE_COLOR");
+
+        if !forced && (var("CLICOLOR").as_deref() == Some("0") || !is_tty) {
+            return ColorLevel::None;
+        }
+
+        let level = Self::terminal_level(&var);
+
+        if forced {
+            level.max(ColorLevel::Ansi16)
+        } else if level == ColorLevel::None && enabled("CLICOLOR") {
+            ColorLevel::Ansi16
+        } else {
+            level
+        }
+    }
+
+    // What the terminal itself advertises through TERM, COLORTERM and friends.
+    fn terminal_level(var: &impl Fn(&str) -> Option<String>) -> ColorLevel {
+        let term = var("TERM").unwrap_or_default().to_lowercase();
+        let colorterm = var("COLORTERM").unwrap_or_default().to_lowercase();
+
+        if term == "dumb" {
+            return ColorLevel::None;
+        }
+
+        if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") {
+            return ColorLevel::TrueColor;
+        }
+
+        // TERM_PROGRAM names the outer terminal, which tmux may not pass truecolor through to
+        if var("TMUX").is_none() {
+            let program = var("TERM_PROGRAM").unwrap_or_default();
+
+            if matches!(program.as_str(), "iTerm.app" | "WezTerm" | "vscode" | "ghostty")
+                || var("WT_SESSION").is_some()
+            {
+                return ColorLevel::TrueColor;
+            }
+        }
+
+        if term.contains("256color") {
+            return ColorLevel::Ansi256;
+        }
+
+        if var("TMUX").is_some() {
+            return ColorLevel::Ansi256;
+        }
+
+        let basic = ["xterm", "screen", "tmux", "vt100", "rxvt", "linux", "ansi", "cygwin", "konsole", "putty"];
+
+        if !colorterm.is_empty() || term.contains("color") || basic.iter().any(|name| term.starts_with(name)) {
+            return ColorLevel::Ansi16;
+        }
+
+        if term.is_empty() && cfg!(windows) {
+            return ColorLevel::Ansi16;
+        }
+
+        ColorLevel::None
+    }
+
+    // Forces every stream to `level`, or restores detection with `None`.
+    pub fn set_override(level: Option<ColorLevel>) {
+        let value = match level {
+            Some(level) => level as u8 + 1,
+            None => 0,
+        };
+
+        OVERRIDE.store(value, Ordering::Relaxed);
+    }
+
+    pub fn get_override() -> Option<ColorLevel> {
+        match OVERRIDE.load(Ordering::Relaxed) {
+            1 => Some(ColorLevel::None),
+            2 => Some(ColorLevel::Ansi16),
+            3 => Some(ColorLevel::Ansi256),
+            4 => Some(ColorLevel::TrueColor),
+            _ => None,
+        }
+    }
+
+    pub fn has_color(&self) -> bool {
+        *self != ColorLevel::None
+    }
+}
+
+// The background named by `COLORFGBG`, which rxvt, Konsole and others export as `fg;bg`
+// (or `fg;default;bg`) palette indices.
+pub fn background_from_env() -> Option<Color> {
+    background_from_vars(|name| std::env::var(name).ok())
+}
+
+pub fn background_from_vars(var: impl Fn(&str) -> Option<String>) -> Option<Color> {
+    let value = var("COLORFGBG")?;
+    let index: usize = value.rsplit(';').next()?.trim().parse().ok()?;
+
+    BASIC.get(index).copied()
+}
+
+impl ColorChoice {
+    // Applies the choice globally: `always` keeps what the terminal advertises but at least 16 colors,
+    // `never` disables color and `auto` goes back to detection.
+    pub fn apply(&self) {
+        match self {
+            ColorChoice::Auto => ColorLevel::set_override(None),
+            ColorChoice::Never => ColorLevel::set_override(Some(ColorLevel::None)),
+            ColorChoice::Always => ColorLevel::set_override(Some(self.level(Stream::Stdout))),
+        }
+    }
+
+    // The level the choice gives `stream`, without changing any global state.
+    pub fn level(&self, stream: Stream) -> ColorLevel {
+        match self {
+            ColorChoice::Auto => ColorLevel::detect(stream),
+            ColorChoice::Never => ColorLevel::None,
+            ColorChoice::Always => ColorLevel::terminal_level(&|name: &str| std::env::var(name).ok()).max(ColorLevel::Ansi16),
+        }
+    }
+
+    pub fn possible_values() -> [&'static str; 3] {
+        ["auto", "always", "never"]
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" | "yes" | "force" => Ok(ColorChoice::Always),
+            "never" | "no" | "none" => Ok(ColorChoice::Never),
+            _ => Err(format!("Unknown color choice: '{}', expected auto, always or never", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(vars: &[(&str, &str)], is_tty: bool) -> ColorLevel {
+        ColorLevel::from_vars(|name| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string()), is_tty)
+    }
+
+    #[test]
+    fn no_color_wins_over_everything() {
+        assert_eq!(level(&[("NO_COLOR", "1"), ("FORCE_COLOR", "1"), ("COLORTERM", "truecolor")], true), ColorLevel::None);
+        assert_eq!(level(&[("NO_COLOR", ""), ("TERM", "xterm")], true), ColorLevel::Ansi16);
+    }
+
+    #[test]
+    fn force_color_enables_color_without_a_terminal() {
+        assert_eq!(level(&[("TERM", "xterm-256color")], false), ColorLevel::None);
+        assert_eq!(level(&[("FORCE_COLOR", "1"), ("TERM", "xterm-256color")], false), ColorLevel::Ansi256);
+        assert_eq!(level(&[("FORCE_COLOR", "1")], false), ColorLevel::Ansi16);
+        assert_eq!(level(&[("FORCE_COLOR", "0")], false), ColorLevel::None);
+    }
+
+    #[test]
+    fn colorterm_and_term_set_the_level() {
+        assert_eq!(level(&[("COLORTERM", "truecolor"), ("TERM", "xterm")], true), ColorLevel::TrueColor);
+        assert_eq!(level(&[("COLORTERM", "24bit")], true), ColorLevel::TrueColor);
+        assert_eq!(level(&[("TERM", "screen-256color")], true), ColorLevel::Ansi256);
+        assert_eq!(level(&[("TERM", "vt100")], true), ColorLevel::Ansi16);
+        assert_eq!(level(&[("TERM", "dumb"), ("COLORTERM", "truecolor")], true), ColorLevel::None);
+        assert_eq!(level(&[("TERM", "dumb"), ("FORCE_COLOR", "1")], true), ColorLevel::Ansi16);
+    }
+}
@@ -0,0 +1,530 @@
+use crate::{background_from_env, Color, ColorLevel, Stream, Style};
+
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Role {
+    Error,
+    Warning,
+    Success,
+    Info,
+    Muted,
+    Accent,
+    Border,
+    Selection,
+    Title,
+}
+
+impl Role {
+    pub const ALL: [Role; 9] = [
+        Role::Error,
+        Role::Warning,
+        Role::Success,
+        Role::Info,
+        Role::Muted,
+        Role::Accent,
+        Role::Border,
+        Role::Selection,
+        Role::Title,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Error => "error",
+            Role::Warning => "warning",
+            Role::Success => "success",
+            Role::Info => "info",
+            Role::Muted => "muted",
+            Role::Accent => "accent",
+            Role::Border => "border",
+            Role::Selection => "selection",
+            Role::Title => "title",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+
+        Role::ALL
+            .iter()
+            .find(|role| role.name() == name)
+            .copied()
+            .ok_or_else(|| format!("Unknown role: '{}'", s))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub error: Style,
+    pub warning: Style,
+    pub success: Style,
+    pub info: Style,
+    pub muted: Style,
+    pub accent: Style,
+    pub border: Style,
+    pub selection: Style,
+    pub title: Style,
+}
+
+static CURRENT: RwLock<Option<Theme>> = RwLock::new(None);
+
+// Light when `COLORFGBG` says the background is light, dark otherwise. The terminal itself isn't
+// asked, since that needs raw mode and a round trip; broccli's `install_background_theme` queries
+// it through OSC 11 and makes the result the current theme.
+impl Default for Theme {
+    fn default() -> Self {
+        let dark = background_from_env().and_then(|background| background.is_dark()).unwrap_or(true);
+
+        Theme::for_background(dark)
+    }
+}
+
+impl Theme {
+    // For dark terminal backgrounds.
+    pub fn dark() -> Self {
+        Self {
+            name: "dark".to_string(),
+            error: Style::new().fg(Color::BrightRed).bold(),
+            warning: Style::new().fg(Color::BrightYellow),
+            success: Style::new().fg(Color::BrightGreen),
+            info: Style::new().fg(Color::BrightCyan),
+            muted: Style::new().fg(Color::BrightBlack),
+            accent: Style::new().fg(Color::BrightMagenta),
+            border: Style::new().fg(Color::DarkGray),
+            selection: Style::new().fg(Color::Black).bg(Color::Cyan),
+            title: Style::new().fg(Color::BrightWhite).bold(),
+        }
+    }
+
+    // For light terminal backgrounds, using darker shades that keep their contrast on white.
+    pub fn light() -> Self {
+        Self {
+            name: "light".to_string(),
+            error: Style::new().fg(Color::Rgb(175, 0, 0)).bold(),
+            warning: Style::new().fg(Color::Rgb(135, 95, 0)),
+            success: Style::new().fg(Color::Rgb(0, 125, 0)),
+            info: Style::new().fg(Color::Rgb(0, 95, 175)),
+            muted: Style::new().fg(Color::Rgb(110, 110, 110)),
+            accent: Style::new().fg(Color::Rgb(135, 0, 175)),
+            border: Style::new().fg(Color::Rgb(170, 170, 170)),
+            selection: Style::new().fg(Color::White).bg(Color::Blue),
+            title: Style::new().fg(Color::Black).bold(),
+        }
+    }
+
+    pub fn for_background(dark: bool) -> Self {
+        if dark {
+            Theme::dark()
+        } else {
+            Theme::light()
+        }
+    }
+
+    pub fn get(&self, role: Role) -> Style {
+        match role {
+            Role::Error => self.error,
+            Role::Warning => self.warning,
+            Role::Success => self.success,
+            Role::Info => self.info,
+            Role::Muted => self.muted,
+            Role::Accent => self.accent,
+            Role::Border => self.border,
+            Role::Selection => self.selection,
+            Role::Title => self.title,
+        }
+    }
+
+    pub fn set(&mut self, role: Role, style: Style) {
+        let slot = match role {
+            Role::Error => &mut self.error,
+            Role::Warning => &mut self.warning,
+            Role::Success => &mut self.success,
+            Role::Info => &mut self.info,
+            Role::Muted => &mut self.muted,
+            Role::Accent => &mut self.accent,
+            Role::Border => &mut self.border,
+            Role::Selection => &mut self.selection,
+            Role::Title => &mut self.title,
+        };
+
+        *slot = style;
+    }
+
+    pub fn with(mut self, role: Role, style: Style) -> Self {
+        self.set(role, style);
+        self
+    }
+
+    // Styles `text` for `role`, downsampled to what stdout supports.
+    pub fn paint(&self, role: Role, text: &str) -> String {
+        self.get(role).paint_for(text, ColorLevel::detect(Stream::Stdout))
+    }
+
+    // The theme set with `set_current`, or the default for the terminal's background.
+    pub fn current() -> Theme {
+        CURRENT
+            .read()
+            .ok()
+            .and_then(|current| current.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_current(theme: Theme) {
+        if let Ok(mut current) = CURRENT.write() {
+            *current = Some(theme);
+        }
+    }
+
+    // Reads a `.toml` or `.json` theme file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Theme, String> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Theme::from_json(&text),
+            Some("toml") => Theme::from_toml(&text),
+            _ => Err(format!("Unknown theme format: '{}', expected .toml or .json", path.display())),
+        }
+    }
+
+    // A flat subset of TOML: `key = "value"` lines and `#` comments. Keys are bare or quoted,
+    // values are basic strings with escapes or literal strings. Tables, arrays, multi-line
+    // strings and anything else are rejected rather than guessed at.
+    pub fn from_toml(text: &str) -> Result<Theme, String> {
+        let mut entries = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let mut parser = Parser { chars: line.chars().collect(), position: 0 };
+            let entry = parser.toml_line().map_err(|e| format!("Line {}: {}", number + 1, e))?;
+            entries.extend(entry);
+        }
+
+        Theme::from_entries(entries)
+    }
+
+    // A single JSON object whose values are all strings; nested objects, arrays, numbers
+    // and the other JSON values are rejected.
+    pub fn from_json(text: &str) -> Result<Theme, String> {
+        let mut parser = Parser { chars: text.chars().collect(), position: 0 };
+        let entries = parser.json_object()?;
+        parser.whitespace();
+
+        if parser.position < parser.chars.len() {
+            return Err(format!("Unexpected trailing characters at {}", parser.position));
+        }
+
+        Theme::from_entries(entries)
+    }
+
+    // `name` names the theme, `base` picks the built-in theme that unlisted roles come from,
+    // and every other key must be a role.
+    pub(crate) fn from_entries(entries: Vec<(String, String)>) -> Result<Theme, String> {
+        let base = entries.iter().find(|(key, _)| key == "base").map(|(_, value)| value.as_str());
+
+        let mut theme = match base {
+            None | Some("dark") => Theme::dark(),
+            Some("light") => Theme::light(),
+            Some(other) => return Err(format!("Unknown base theme: '{}', expected dark or light", other)),
+        };
+
+        theme.name = "custom".to_string();
+
+        for (i, (key, _)) in entries.iter().enumerate() {
+            if entries[..i].iter().any(|(other, _)| other == key) {
+                return Err(format!("Duplicate key '{}'", key));
+            }
+        }
+
+        for (key, value) in entries {
+            match key.as_str() {
+                "base" => {}
+                "name" => theme.name = value,
+                _ => {
+                    let role = key.parse::<Role>()?;
+                    let style = value.parse::<Style>().map_err(|e| format!("Role '{}': {}", key, e))?;
+                    theme.set(role, style);
+                }
+            }
+        }
+
+        Ok(theme)
+    }
+}
+
+// Just enough TOML and JSON for theme files: flat tables of strings.
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    fn whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.whitespace();
+
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(c) => Err(format!("Expected '{}' but found '{}' at {}", expected, c, self.position)),
+            None => Err(format!("Expected '{}' but the input ended", expected)),
+        }
+    }
+
+    // `count` hex digits of a `\u` or `\U` escape.
+    fn hex(&mut self, count: usize) -> Result<u32, String> {
+        let digits: String = self.chars.iter().skip(self.position).take(count).collect();
+
+        if digits.chars().count() < count || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid unicode escape '{}'", digits));
+        }
+
+        self.position += count;
+        u32::from_str_radix(&digits, 16).map_err(|e| e.to_string())
+    }
+
+    // One line: blank, a comment, or `key = value` with an optional trailing comment.
+    fn toml_line(&mut self) -> Result<Option<(String, String)>, String> {
+        self.whitespace();
+
+        match self.peek() {
+            None | Some('#') => return Ok(None),
+            Some('[') => return Err("tables are not supported, put every key at the top level".to_string()),
+            _ => {}
+        }
+
+        let key = match self.peek() {
+            Some('"') => self.toml_basic_string()?,
+            Some('\'') => self.toml_literal_string()?,
+            _ => {
+                let start = self.position;
+
+                while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                    self.position += 1;
+                }
+
+                if self.position == start {
+                    return Err("expected 'key = \"value\"'".to_string());
+                }
+
+                self.chars[start..self.position].iter().collect()
+            }
+        };
+
+        self.expect('=')?;
+        self.whitespace();
+
+        let value = match self.peek() {
+            Some('"') => self.toml_basic_string()?,
+            Some('\'') => self.toml_literal_string()?,
+            _ => return Err(format!("the value of '{}' must be a quoted string", key)),
+        };
+
+        self.whitespace();
+
+        match self.peek() {
+            None | Some('#') => Ok(Some((key, value))),
+            Some(c) => Err(format!("unexpected '{}' after the value of '{}'", c, key)),
+        }
+    }
+
+    fn toml_basic_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+
+        loop {
+            match self.next().ok_or("Unterminated string")? {
+                '"' => return Ok(out),
+                '\\' => out.push(match self.next().ok_or("Unterminated string")? {
+                    'b' => '\u{8}',
+                    't' => '\t',
+                    'n' => '\n',
+                    'f' => '\u{c}',
+                    'r' => '\r',
+                    '"' => '"',
+                    '\\' => '\\',
+                    'u' => char::from_u32(self.hex(4)?).ok_or("Invalid unicode escape")?,
+                    'U' => char::from_u32(self.hex(8)?).ok_or("Invalid unicode escape")?,
+                    other => return Err(format!("Invalid escape '\\{}'", other)),
+                }),
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn toml_literal_string(&mut self) -> Result<String, String> {
+        self.expect('\'')?;
+        let mut out = String::new();
+
+        loop {
+            match self.next().ok_or("Unterminated string")? {
+                '\'' => return Ok(out),
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn json_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+
+        loop {
+            match self.next().ok_or("Unterminated string")? {
+                '"' => return Ok(out),
+                '\\' => out.push(match self.next().ok_or("Unterminated string")? {
+                    'b' => '\u{8}',
+                    't' => '\t',
+                    'n' => '\n',
+                    'f' => '\u{c}',
+                    'r' => '\r',
+                    '"' => '"',
+                    '\\' => '\\',
+                    '/' => '/',
+                    'u' => self.json_unicode()?,
+                    other => return Err(format!("Invalid escape '\\{}'", other)),
+                }),
+                c if (c as u32) < 0x20 => return Err("Control characters must be escaped in strings".to_string()),
+                c => out.push(c),
+            }
+        }
+    }
+
+    // After `\u`: a code point, or a UTF-16 surrogate pair spelled as two escapes.
+    fn json_unicode(&mut self) -> Result<char, String> {
+        let first = self.hex(4)?;
+
+        let code = if (0xD800..0xDC00).contains(&first) {
+            if self.next() != Some('\\') || self.next() != Some('u') {
+                return Err("Unpaired surrogate in unicode escape".to_string());
+            }
+
+            let second = self.hex(4)?;
+
+            if !(0xDC00..0xE000).contains(&second) {
+                return Err("Unpaired surrogate in unicode escape".to_string());
+            }
+
+            0x10000 + ((first - 0xD800) << 10) + (second - 0xDC00)
+        } else {
+            first
+        };
+
+        char::from_u32(code).ok_or_else(|| "Unpaired surrogate in unicode escape".to_string())
+    }
+
+    fn json_object(&mut self) -> Result<Vec<(String, String)>, String> {
+        let mut entries = Vec::new();
+
+        self.expect('{')?;
+        self.whitespace();
+
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(entries);
+        }
+
+        loop {
+            let key = self.json_string()?;
+            self.expect(':')?;
+            self.whitespace();
+
+            if self.peek() != Some('"') {
+                return Err(format!("Expected a string for '{}'", key));
+            }
+
+            let value = self.json_string()?;
+            entries.push((key, value));
+            self.whitespace();
+
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(entries),
+                _ => return Err(format!("Expected ',' or '}}' at {}", self.position.saturating_sub(1))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn role_errors_quote_the_input_as_typed() {
+        assert_eq!(" Error ".parse::<Role>(), Ok(Role::Error));
+        assert_eq!("Errr".parse::<Role>(), Err("Unknown role: 'Errr'".to_string()));
+    }
+
+    #[test]
+    fn toml_reads_flat_keys_and_escapes() {
+        let theme = Theme::from_toml(
+            "# comment\nbase = 'light'\nname = \"Tab\\there \\u00e9\" # trailing\n\"error\" = \"bold red\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(theme.name, "Tab\there é");
+        assert_eq!(theme.error, "bold red".parse().unwrap());
+        assert_eq!(theme.warning, Theme::light().warning);
+    }
+
+    #[test]
+    fn toml_rejects_what_it_does_not_understand() {
+        for text in [
+            "error = \"x\" y\"",
+            "[styles]\nerror = \"red\"",
+            "error = red",
+            "error = \"red",
+            "name = \"\\q\"",
+            "name = \"\\u12\"",
+            "error = \"red\"\nerror = \"blue\"",
+        ] {
+            assert!(Theme::from_toml(text).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn json_handles_every_escape() {
+        let theme = Theme::from_json(r#"{ "name": "\b\f\n\/\"\u00e9\ud83c\udfa8", "error": "red" }"#).unwrap();
+
+        assert_eq!(theme.name, "\u{8}\u{c}\n/\"é🎨");
+        assert_eq!(theme.error, "red".parse().unwrap());
+    }
+
+    #[test]
+    fn json_rejects_what_it_does_not_understand() {
+        for text in [
+            r#"{ "name": "\u12" }"#,
+            r#"{ "name": "\ud83c" }"#,
+            r#"{ "name": "\udfa8" }"#,
+            r#"{ "styles": { "error": "red" } }"#,
+            r#"{ "error": "red", }"#,
+            r#"{ "error": "red" } x"#,
+            r#"{ "error": "red", "error": "blue" }"#,
+        ] {
+            assert!(Theme::from_json(text).is_err(), "{}", text);
+        }
+    }
+}
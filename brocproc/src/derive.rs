@@ -0,0 +1,456 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Expr, Fields, GenericArgument, Lit, LitChar, LitStr, PathArguments, Type};
+
+enum FieldKind {
+    Flag,
+    Optional(Type),
+    Multiple(Type),
+    Required(Type),
+}
+
+#[derive(Default)]
+struct ArgAttrs {
+    short: Option<char>,
+    long: Option<String>,
+    default: Option<Expr>,
+    env: Option<String>,
+    value_name: Option<String>,
+    conflicts: Vec<String>,
+    possible_values: Vec<String>,
+    required: bool,
+    count: bool,
+}
+
+#[derive(Default)]
+struct CommandAttrs {
+    name: Option<String>,
+    version: Option<Option<String>>,
+    about: Option<String>,
+}
+
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(lit) => Some(lit.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    // Only the first paragraph is used as help text
+    let text = lines
+        .split(|line| line.is_empty())
+        .find(|paragraph| !paragraph.is_empty())?
+        .join(" ");
+
+    Some(text)
+}
+
+fn inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last()?;
+
+    if segment.ident != wrapper {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn field_kind(ty: &Type) -> FieldKind {
+    if let Type::Path(path) = ty {
+        if path.path.is_ident("bool") {
+            return FieldKind::Flag;
+        }
+    }
+
+    if let Some(inner) = inner_type(ty, "Option") {
+        return FieldKind::Optional(inner.clone());
+    }
+
+    if let Some(inner) = inner_type(ty, "Vec") {
+        return FieldKind::Multiple(inner.clone());
+    }
+
+    FieldKind::Required(ty.clone())
+}
+
+fn parse_arg_attrs(attrs: &[Attribute], field_name: &str) -> syn::Result<ArgAttrs> {
+    let mut parsed = ArgAttrs::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("arg")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("short") {
+                parsed.short = Some(if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<LitChar>()?.value()
+                } else {
+                    field_name.chars().next().unwrap_or('_')
+                });
+            } else if meta.path.is_ident("long") {
+                parsed.long = Some(if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<LitStr>()?.value()
+                } else {
+                    field_name.replace('_', "-")
+                });
+            } else if meta.path.is_ident("default") {
+                parsed.default = Some(meta.value()?.parse::<Expr>()?);
+            } else if meta.path.is_ident("env") {
+                parsed.env = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("value_name") {
+                parsed.value_name = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("conflicts_with") {
+                parsed.conflicts.push(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("possible_values") {
+                let content;
+                meta.value()?;
+                syn::bracketed!(content in meta.input);
+
+                let values = content.parse_terminated(|input| input.parse::<LitStr>(), syn::Token![,])?;
+                parsed.possible_values = values.iter().map(|value| value.value()).collect();
+            } else if meta.path.is_ident("required") {
+                parsed.required = true;
+            } else if meta.path.is_ident("count") {
+                parsed.count = true;
+            } else {
+                return Err(meta.error("unsupported `arg` attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(parsed)
+}
+
+fn parse_command_attrs(attrs: &[Attribute]) -> syn::Result<CommandAttrs> {
+    let mut parsed = CommandAttrs::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("command")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                parsed.name = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("version") {
+                parsed.version = Some(if meta.input.peek(syn::Token![=]) {
+                    Some(meta.value()?.parse::<LitStr>()?.value())
+                } else {
+                    None
+                });
+            } else if meta.path.is_ident("about") {
+                parsed.about = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else {
+                return Err(meta.error("unsupported `command` attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(parsed)
+}
+
+pub fn expand_parser(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(input, "`Parser` can only be derived for structs"));
+    };
+
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(input, "`Parser` requires a struct with named fields"));
+    };
+
+    let command_attrs = parse_command_attrs(&input.attrs)?;
+
+    let name = match command_attrs.name {
+        Some(name) => quote! { #name },
+        None => quote! { env!("CARGO_PKG_NAME") },
+    };
+
+    let version = match command_attrs.version {
+        Some(Some(version)) => quote! { .version(#version) },
+        Some(None) => quote! { .version(env!("CARGO_PKG_VERSION")) },
+        None => quote! {},
+    };
+
+    let about = match command_attrs.about.or_else(|| doc_comment(&input.attrs)) {
+        Some(about) => quote! { .about(#about) },
+        None => quote! {},
+    };
+
+    let mut args = Vec::new();
+    let mut initializers = Vec::new();
+    // The field that claimed each flag, so two fields can't share `-v` or `--name`
+    let mut shorts: Vec<(char, String)> = Vec::new();
+    let mut longs: Vec<(String, String)> = Vec::new();
+
+    for field in &fields.named {
+        let ident = field.ident.as_ref().unwrap();
+        let field_name = ident.to_string();
+        let attrs = parse_arg_attrs(&field.attrs, &field_name)?;
+        let kind = field_kind(&field.ty);
+
+        if let Some(short) = attrs.short {
+            if let Some((_, other)) = shorts.iter().find(|(used, _)| *used == short) {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    format!("`-{}` is already used by `{}`, pick another with `#[arg(short = '…')]`", short, other),
+                ));
+            }
+
+            shorts.push((short, field_name.clone()));
+        }
+
+        if let Some(long) = &attrs.long {
+            if let Some((_, other)) = longs.iter().find(|(used, _)| used == long) {
+                return Err(syn::Error::new_spanned(field, format!("`--{}` is already used by `{}`", long, other)));
+            }
+
+            longs.push((long.clone(), field_name.clone()));
+        }
+
+        let mut builder = quote! { broccli::Arg::new(#field_name) };
+
+        if let Some(short) = attrs.short {
+            builder.extend(quote! { .short(#short) });
+        }
+
+        if let Some(long) = &attrs.long {
+            builder.extend(quote! { .long(#long) });
+        }
+
+        if let Some(help) = doc_comment(&field.attrs) {
+            builder.extend(quote! { .help(#help) });
+        }
+
+        if let Some(value_name) = &attrs.value_name {
+            builder.extend(quote! { .value_name(#value_name) });
+        }
+
+        if let Some(env) = &attrs.env {
+            builder.extend(quote! { .env(#env) });
+        }
+
+        for conflict in &attrs.conflicts {
+            builder.extend(quote! { .conflicts_with(#conflict) });
+        }
+
+        if !attrs.possible_values.is_empty() {
+            let values = &attrs.possible_values;
+            builder.extend(quote! { .possible_values(&[#(#values),*]) });
+        }
+
+        if let Some(default) = &attrs.default {
+            builder.extend(quote! { .default_value(&::std::string::ToString::to_string(&#default)) });
+        }
+
+        let is_flag = attrs.short.is_some() || attrs.long.is_some();
+
+        let initializer = match &kind {
+            FieldKind::Required(ty) if attrs.count => {
+                if !is_flag {
+                    return Err(syn::Error::new_spanned(field, "`count` fields need `#[arg(short)]` or `#[arg(long)]`"));
+                }
+
+                if attrs.required || attrs.default.is_some() {
+                    return Err(syn::Error::new_spanned(field, "`count` can't be combined with `required` or `default`"));
+                }
+
+                builder.extend(quote! { .multiple(true) });
+
+                quote! { matches.occurrences(#field_name) as #ty }
+            }
+            _ if attrs.count => {
+                return Err(syn::Error::new_spanned(field, "`count` needs an integer field"));
+            }
+            FieldKind::Flag => {
+                if attrs.required {
+                    return Err(syn::Error::new_spanned(field, "`bool` fields are flags and can't be `required`"));
+                }
+
+                if !is_flag {
+                    return Err(syn::Error::new_spanned(field, "`bool` fields need `#[arg(short)]` or `#[arg(long)]`"));
+                }
+
+                quote! { matches.flag(#field_name) }
+            }
+            FieldKind::Optional(ty) => {
+                if attrs.required {
+                    return Err(syn::Error::new_spanned(field, "`Option` fields are optional, drop `required` or the `Option`"));
+                }
+
+                builder.extend(quote! { .value_parser::<#ty>() });
+
+                quote! { matches.try_get::<#ty>(#field_name)? }
+            }
+            FieldKind::Multiple(ty) => {
+                builder.extend(quote! { .value_parser::<#ty>().multiple(true) });
+
+                if attrs.required {
+                    builder.extend(quote! { .required(true) });
+                }
+
+                quote! { matches.try_get_many::<#ty>(#field_name)? }
+            }
+            FieldKind::Required(ty) => {
+                if attrs.required && attrs.default.is_some() {
+                    return Err(syn::Error::new_spanned(field, "a field with a `default` can't also be `required`"));
+                }
+
+                builder.extend(quote! { .value_parser::<#ty>() });
+
+                if attrs.default.is_none() {
+                    builder.extend(quote! { .required(true) });
+                }
+
+                quote! {
+                    matches.try_get::<#ty>(#field_name)?.ok_or_else(|| {
+                        broccli::ArgError::new(
+                            broccli::ArgErrorKind::MissingRequired,
+                            format!("the argument '{}' was not provided", #field_name),
+                        )
+                    })?
+                }
+            }
+        };
+
+        args.push(builder);
+        initializers.push(quote! { #ident: #initializer });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics broccli::Parser for #ident #ty_generics #where_clause {
+            fn command() -> broccli::Command {
+                broccli::Command::new(#name)
+                    #version
+                    #about
+                    #(.arg(#args))*
+            }
+
+            fn from_matches(matches: &broccli::ArgMatches) -> Result<Self, broccli::ArgError> {
+                Ok(Self {
+                    #(#initializers),*
+                })
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn error(input: DeriveInput) -> String {
+        expand_parser(&input).err().map(|e| e.to_string()).unwrap_or_default()
+    }
+
+    #[test]
+    fn automatic_short_flags_must_not_collide() {
+        let message = error(parse_quote! {
+            struct Cli {
+                #[arg(short)]
+                verbose: bool,
+                #[arg(short, long)]
+                version: bool,
+            }
+        });
+
+        assert!(message.contains("`-v` is already used by `verbose`"), "{}", message);
+    }
+
+    #[test]
+    fn long_flags_must_not_collide() {
+        let message = error(parse_quote! {
+            struct Cli {
+                #[arg(long = "out")]
+                output: Option<String>,
+                #[arg(long = "out")]
+                other: Option<String>,
+            }
+        });
+
+        assert!(message.contains("`--out` is already used by `output`"), "{}", message);
+    }
+
+    #[test]
+    fn required_is_rejected_where_it_cannot_apply() {
+        let optional = error(parse_quote! {
+            struct Cli {
+                #[arg(long, required)]
+                name: Option<String>,
+            }
+        });
+
+        let flag = error(parse_quote! {
+            struct Cli {
+                #[arg(long, required)]
+                force: bool,
+            }
+        });
+
+        let defaulted = error(parse_quote! {
+            struct Cli {
+                #[arg(long, required, default = 3)]
+                jobs: u32,
+            }
+        });
+
+        assert!(optional.contains("drop `required`"), "{}", optional);
+        assert!(flag.contains("can't be `required`"), "{}", flag);
+        assert!(defaulted.contains("can't also be `required`"), "{}", defaulted);
+    }
+
+    #[test]
+    fn counts_need_an_integer_flag() {
+        let positional = error(parse_quote! {
+            struct Cli {
+                #[arg(count)]
+                verbose: u8,
+            }
+        });
+
+        let optional = error(parse_quote! {
+            struct Cli {
+                #[arg(short, count)]
+                verbose: Option<u8>,
+            }
+        });
+
+        assert!(positional.contains("need `#[arg(short)]`"), "{}", positional);
+        assert!(optional.contains("needs an integer field"), "{}", optional);
+    }
+
+    #[test]
+    fn valid_structs_expand() {
+        let input: DeriveInput = parse_quote! {
+            struct Cli {
+                #[arg(short, count)]
+                verbose: u8,
+                #[arg(short = 'V', long)]
+                version: bool,
+                #[arg(long, required)]
+                files: Vec<String>,
+            }
+        };
+
+        assert!(expand_parser(&input).is_ok());
+    }
+}
@@ -1,7 +1,8 @@
+mod derive;
 mod parse;
 mod xformat;
 use quote::quote;
-use syn::parse_macro_input;
+use syn::{parse_macro_input, DeriveInput};
 use xformat::*;
 use broccolor::{Color, TextStyle};
 use crate::parse::MacroArgs;
@@ -46,4 +47,13 @@ pub fn xprintb(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     quote! {
         println!("{}", #out)
     }.into()
+}
+
+#[proc_macro_derive(Parser, attributes(arg, command))]
+pub fn derive_parser(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    derive::expand_parser(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
 }
\ No newline at end of file
@@ -0,0 +1,200 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+pub(crate) type Validator = fn(&str) -> Result<(), String>;
+pub(crate) type ValueCompleter = fn(&str) -> Vec<String>;
+
+fn validate<T: FromStr>(value: &str) -> Result<(), String>
+where
+    T::Err: Display,
+{
+    value.parse::<T>().map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[derive(Clone)]
+pub struct Arg {
+    pub(crate) name: String,
+    pub(crate) short: Option<char>,
+    pub(crate) long: Option<String>,
+    pub(crate) help: Option<String>,
+    pub(crate) value_name: Option<String>,
+    pub(crate) takes_value: bool,
+    pub(crate) multiple: bool,
+    pub(crate) required: bool,
+    pub(crate) global: bool,
+    pub(crate) default: Option<String>,
+    pub(crate) env: Option<String>,
+    pub(crate) conflicts: Vec<String>,
+    pub(crate) possible_values: Vec<String>,
+    pub(crate) validator: Option<Validator>,
+    pub(crate) completer: Option<ValueCompleter>,
+}
+
+impl Arg {
+    // Args without a short or long name are positional, matched in the order they're added.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            short: None,
+            long: None,
+            help: None,
+            value_name: None,
+            takes_value: false,
+            multiple: false,
+            required: false,
+            global: false,
+            default: None,
+            env: None,
+            conflicts: Vec::new(),
+            possible_values: Vec::new(),
+            validator: None,
+            completer: None,
+        }
+    }
+
+    pub fn short(mut self, short: char) -> Self {
+        self.short = Some(short);
+        self
+    }
+
+    pub fn long(mut self, long: &str) -> Self {
+        self.long = Some(long.to_string());
+        self
+    }
+
+    pub fn help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    pub fn value_name(mut self, value_name: &str) -> Self {
+        self.value_name = Some(value_name.to_string());
+        self.takes_value = true;
+        self
+    }
+
+    pub fn takes_value(mut self, takes_value: bool) -> Self {
+        self.takes_value = takes_value;
+        self
+    }
+
+    pub fn multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    // Global args are inherited by every subcommand and can be given at any level.
+    pub fn global(mut self, global: bool) -> Self {
+        self.global = global;
+        self
+    }
+
+    pub fn default_value(mut self, default: &str) -> Self {
+        self.default = Some(default.to_string());
+        self.takes_value = true;
+        self
+    }
+
+    pub fn env(mut self, env: &str) -> Self {
+        self.env = Some(env.to_string());
+        self
+    }
+
+    pub fn conflicts_with(mut self, name: &str) -> Self {
+        self.conflicts.push(name.to_string());
+        self
+    }
+
+    pub fn possible_values(mut self, values: &[&str]) -> Self {
+        self.possible_values = values.iter().map(|v| v.to_string()).collect();
+        self.takes_value = true;
+        self
+    }
+
+    // Rejects values that don't parse as `T` while parsing the command line.
+    pub fn value_parser<T: FromStr>(mut self) -> Self
+    where
+        T::Err: Display,
+    {
+        self.validator = Some(validate::<T>);
+        self.takes_value = true;
+        self
+    }
+
+    // Supplies values at completion time, given the word being completed.
+    pub fn value_completer(mut self, completer: fn(&str) -> Vec<String>) -> Self {
+        self.completer = Some(completer);
+        self.takes_value = true;
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_short(&self) -> Option<char> {
+        self.short
+    }
+
+    pub fn get_long(&self) -> Option<&str> {
+        self.long.as_deref()
+    }
+
+    pub fn get_help(&self) -> Option<&str> {
+        self.help.as_deref()
+    }
+
+    pub fn get_default(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    pub fn get_env(&self) -> Option<&str> {
+        self.env.as_deref()
+    }
+
+    pub fn get_possible_values(&self) -> &[String] {
+        &self.possible_values
+    }
+
+    pub fn is_positional(&self) -> bool {
+        self.short.is_none() && self.long.is_none()
+    }
+
+    pub fn is_flag(&self) -> bool {
+        !self.is_positional() && !self.takes_value
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    pub fn is_global(&self) -> bool {
+        self.global
+    }
+
+    pub fn is_multiple(&self) -> bool {
+        self.multiple
+    }
+
+    pub fn get_value_name(&self) -> String {
+        self.value_name.clone().unwrap_or_else(|| self.name.to_uppercase())
+    }
+
+    // How the arg is referred to in usage lines and error messages
+    pub fn display_name(&self) -> String {
+        let dots = if self.multiple { "..." } else { "" };
+
+        match (&self.long, self.short) {
+            (Some(long), _) if self.takes_value => format!("--{} <{}>{}", long, self.get_value_name(), dots),
+            (Some(long), _) => format!("--{}", long),
+            (None, Some(short)) if self.takes_value => format!("-{} <{}>{}", short, self.get_value_name(), dots),
+            (None, Some(short)) => format!("-{}", short),
+            (None, None) => format!("<{}>{}", self.get_value_name(), dots),
+        }
+    }
+}
@@ -0,0 +1,826 @@
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use broccolor::{Color, ColorChoice, Stream, Style};
+use super::arg::Arg;
+use super::error::{paint, quoted, ArgError, ArgErrorKind};
+use super::help::HelpTheme;
+use super::matches::{ArgMatches, ExternalSubcommand, ValueSource};
+use super::suggest::suggest;
+
+#[derive(Clone)]
+pub struct Command {
+    pub(crate) name: String,
+    pub(crate) version: Option<String>,
+    pub(crate) about: Option<String>,
+    pub(crate) args: Vec<Arg>,
+    pub(crate) subcommands: Vec<Command>,
+    pub(crate) aliases: Vec<String>,
+    pub(crate) hidden: bool,
+    pub(crate) subcommand_required: bool,
+    pub(crate) allow_external: bool,
+    pub(crate) help_theme: Option<HelpTheme>,
+    pub(crate) examples: Vec<(String, String)>,
+    pub(crate) man_flag: bool,
+    // Globals copied from the parent, whose `required` check is left to the parent.
+    pub(crate) inherited: Vec<String>,
+}
+
+impl Command {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: None,
+            about: None,
+            args: Vec::new(),
+            subcommands: Vec::new(),
+            aliases: Vec::new(),
+            hidden: false,
+            subcommand_required: false,
+            allow_external: false,
+            help_theme: None,
+            examples: Vec::new(),
+            man_flag: false,
+            inherited: Vec::new(),
+        }
+    }
+
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+
+    pub fn about(mut self, about: &str) -> Self {
+        self.about = Some(about.to_string());
+        self
+    }
+
+    pub fn arg(mut self, arg: Arg) -> Self {
+        self.args.push(arg);
+        self
+    }
+
+    pub fn args(mut self, args: impl IntoIterator<Item = Arg>) -> Self {
+        self.args.extend(args);
+        self
+    }
+
+    pub fn subcommand(mut self, subcommand: Command) -> Self {
+        self.subcommands.push(subcommand);
+        self
+    }
+
+    pub fn subcommands(mut self, subcommands: impl IntoIterator<Item = Command>) -> Self {
+        self.subcommands.extend(subcommands);
+        self
+    }
+
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.aliases.push(alias.to_string());
+        self
+    }
+
+    // Hidden commands still parse but are left out of help, suggestions and completions.
+    pub fn hide(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    pub fn subcommand_required(mut self, required: bool) -> Self {
+        self.subcommand_required = required;
+        self
+    }
+
+    // Unknown subcommands run `<name>-<subcommand>` from `PATH` when it exists.
+    pub fn allow_external_subcommands(mut self, allow: bool) -> Self {
+        self.allow_external = allow;
+        self
+    }
+
+    // Subcommands without their own theme use their parent's.
+    pub fn help_theme(mut self, theme: HelpTheme) -> Self {
+        self.help_theme = Some(theme);
+        self
+    }
+
+    // Shown in the EXAMPLES section of the man page.
+    pub fn example(mut self, command: &str, description: &str) -> Self {
+        self.examples.push((command.to_string(), description.to_string()));
+        self
+    }
+
+    // Makes `<bin> --generate-man` print the man page and exit.
+    pub fn man_flag(mut self, enable: bool) -> Self {
+        self.man_flag = enable;
+        self
+    }
+
+    // Adds a global `--color <WHEN>` option taking auto, always or never.
+    pub fn color_flag(mut self, enable: bool) -> Self {
+        self.args.retain(|arg| arg.name != "color");
+
+        if enable {
+            self.args.push(
+                Arg::new("color")
+                    .long("color")
+                    .value_name("WHEN")
+                    .help("When to use colors")
+                    .possible_values(&ColorChoice::possible_values())
+                    .default_value("auto")
+                    .global(true),
+            );
+        }
+
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn get_about(&self) -> Option<&str> {
+        self.about.as_deref()
+    }
+
+    pub fn get_args(&self) -> &[Arg] {
+        &self.args
+    }
+
+    pub fn get_subcommands(&self) -> &[Command] {
+        &self.subcommands
+    }
+
+    pub fn get_examples(&self) -> &[(String, String)] {
+        &self.examples
+    }
+
+    pub fn get_aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn find_subcommand(&self, name: &str) -> Option<&Command> {
+        self.subcommands
+            .iter()
+            .find(|sub| sub.name == name || sub.aliases.iter().any(|alias| alias == name))
+    }
+
+    pub fn usage(&self) -> String {
+        self.usage_for(&self.name)
+    }
+
+    // `path` is the full invocation leading to this command, e.g. `tool db migrate`.
+    pub(crate) fn usage_for(&self, path: &str) -> String {
+        let mut usage = path.to_string();
+
+        if self.args.iter().any(|arg| !arg.is_positional()) {
+            usage.push_str(" [OPTIONS]");
+        }
+
+        for arg in self.args.iter().filter(|arg| !arg.is_positional() && arg.required) {
+            usage.push(' ');
+            usage.push_str(&arg.display_name());
+        }
+
+        for arg in self.args.iter().filter(|arg| arg.is_positional()) {
+            usage.push(' ');
+
+            if arg.required {
+                usage.push_str(&arg.display_name());
+            } else {
+                usage.push_str(&format!("[{}]", arg.display_name()));
+            }
+        }
+
+        if !self.subcommands.is_empty() || self.allow_external {
+            if self.subcommand_required {
+                usage.push_str(" <COMMAND>");
+            } else {
+                usage.push_str(" [COMMAND]");
+            }
+        }
+
+        usage
+    }
+
+    // Parses `std::env::args`, printing the error and exiting on failure.
+    pub fn get_matches(&self) -> ArgMatches {
+        self.get_matches_from(std::env::args())
+    }
+
+    pub fn get_matches_from<I, T>(&self, args: I) -> ArgMatches
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let args: Vec<String> = args.into_iter().map(Into::into).collect();
+        self.handle_completion_request(&args);
+        self.handle_man_request(&args);
+
+        self.try_get_matches_from(args).unwrap_or_else(|e| e.exit())
+    }
+
+    // The first item is the binary name, as in `std::env::args`.
+    pub fn try_get_matches_from<I, T>(&self, args: I) -> Result<ArgMatches, ArgError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let args: Vec<String> = args.into_iter().skip(1).map(Into::into).collect();
+        let color = self.color_choice(&args);
+        let matches = self.parse(&args, &self.name, color).map_err(|e| e.with_color(color))?;
+        self.apply_color_choice(&matches);
+
+        Ok(matches)
+    }
+
+    // The `--color` choice in `args`, read ahead of parsing so help and errors already follow it.
+    // Option values can't start with `--`, so any `--color` here is the option itself.
+    fn color_choice(&self, args: &[String]) -> ColorChoice {
+        let mut choice = ColorChoice::Auto;
+
+        if !self.has_color_flag() {
+            return choice;
+        }
+
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            let value = match arg.as_str() {
+                "--" => break,
+                "--color" => iter.next().map(String::as_str),
+                arg => arg.strip_prefix("--color="),
+            };
+
+            if let Some(parsed) = value.and_then(|value| value.parse().ok()) {
+                choice = parsed;
+            }
+        }
+
+        choice
+    }
+
+    fn has_color_flag(&self) -> bool {
+        self.args.iter().any(|arg| arg.name == "color" && arg.long.as_deref() == Some("color"))
+    }
+
+    // Applies a parsed `--color` only once parsing succeeded, so a failed parse leaves global state alone.
+    fn apply_color_choice(&self, matches: &ArgMatches) {
+        if !self.has_color_flag() {
+            return;
+        }
+
+        if let Some(choice) = matches.value_of("color").and_then(|value| value.parse::<ColorChoice>().ok()) {
+            choice.apply();
+        }
+    }
+
+    fn find_long(&self, long: &str, color: ColorChoice) -> Result<&Arg, ArgError> {
+        self.args
+            .iter()
+            .find(|arg| arg.long.as_deref() == Some(long))
+            .ok_or_else(|| {
+                let mut error = unknown_argument(&format!("--{}", long), color);
+                let longs = self.args.iter().filter_map(|arg| arg.long.as_deref());
+
+                if let Some(similar) = suggest(long, longs) {
+                    error.message.push_str(&format!(
+                        "\n\n  {} a similar argument exists: {}",
+                        paint("tip:", Style::new().fg(Color::Green), Stream::Stderr, color),
+                        quoted(&format!("--{}", similar), color),
+                    ));
+                }
+
+                error
+            })
+    }
+
+    fn find_short(&self, short: char, color: ColorChoice) -> Result<&Arg, ArgError> {
+        self.args
+            .iter()
+            .find(|arg| arg.short == Some(short))
+            .ok_or_else(|| unknown_argument(&format!("-{}", short), color))
+    }
+
+    fn find_external(&self, path: &str, name: &str) -> Option<PathBuf> {
+        let binary = format!("{}-{}{}", path.replace(' ', "-"), name, std::env::consts::EXE_SUFFIX);
+        let paths = std::env::var_os("PATH")?;
+
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(&binary))
+            .find(|candidate| is_executable(candidate))
+    }
+
+    fn unknown_subcommand(&self, name: &str, color: ColorChoice) -> ArgError {
+        let mut error = ArgError::new(
+            ArgErrorKind::UnknownSubcommand,
+            format!("unrecognized subcommand {}", quoted(name, color)),
+        );
+
+        let names = self.subcommands
+            .iter()
+            .filter(|sub| !sub.hidden)
+            .flat_map(|sub| std::iter::once(&sub.name).chain(&sub.aliases))
+            .map(|name| name.as_str());
+
+        if let Some(similar) = suggest(name, names) {
+            error.message.push_str(&format!(
+                "\n\n  {} did you mean {}?",
+                paint("tip:", Style::new().fg(Color::Green), Stream::Stderr, color),
+                quoted(similar, color),
+            ));
+        }
+
+        error
+    }
+
+    // Inherits the help theme and the global args of the parent that the subcommand doesn't redefine.
+    pub(crate) fn inherit(&self, parent: &Command) -> Command {
+        let mut command = self.clone();
+        command.help_theme = command.help_theme.or(parent.help_theme);
+
+        for arg in parent.args.iter().filter(|arg| arg.global) {
+            if !command.args.iter().any(|own| own.name == arg.name) {
+                command.args.push(arg.clone());
+                command.inherited.push(arg.name.clone());
+            }
+        }
+
+        command
+    }
+
+    fn parse(&self, args: &[String], path: &str, color: ColorChoice) -> Result<ArgMatches, ArgError> {
+        self.parse_level(args, path, color).map_err(|e| match (e.usage.is_some(), e.kind) {
+            (true, _) | (_, ArgErrorKind::DisplayHelp) | (_, ArgErrorKind::DisplayVersion) => e,
+            _ => e.with_usage(self.usage_for(path)),
+        })
+    }
+
+    fn display_help(&self, path: &str, color: ColorChoice) -> ArgError {
+        ArgError::new(ArgErrorKind::DisplayHelp, self.render_help_for(path, color.level(Stream::Stdout)))
+    }
+
+    fn display_version(&self, path: &str) -> ArgError {
+        let version = self.version.clone().unwrap_or_default();
+        ArgError::new(ArgErrorKind::DisplayVersion, format!("{} {}", path, version))
+    }
+
+    // Handles `help <subcommand>...` by showing the help of the innermost known subcommand.
+    fn help_subcommand(&self, names: &[String], path: &str, color: ColorChoice) -> ArgError {
+        let mut command = self.clone();
+        let mut path = path.to_string();
+
+        for name in names {
+            let Some(subcommand) = command.find_subcommand(name) else {
+                break;
+            };
+
+            let child = subcommand.inherit(&command);
+            path = format!("{} {}", path, child.name);
+            command = child;
+        }
+
+        command.display_help(&path, color)
+    }
+
+    fn parse_level(&self, args: &[String], path: &str, color: ColorChoice) -> Result<ArgMatches, ArgError> {
+        let mut matches = ArgMatches::default();
+        let positionals: Vec<&Arg> = self.args.iter().filter(|arg| arg.is_positional()).collect();
+        let mut position = 0;
+        let mut only_positional = false;
+        let mut iter = args.iter().enumerate();
+
+        while let Some((index, current)) = iter.next() {
+            if !only_positional && current == "--" {
+                only_positional = true;
+                continue;
+            }
+
+            if !only_positional && current.starts_with("--") {
+                let (name, inline) = match current[2..].split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (&current[2..], None),
+                };
+
+                if self.help_arg().is_some_and(|arg| arg.long.as_deref() == Some(name)) {
+                    return Err(self.display_help(path, color));
+                }
+
+                if self.version_arg().is_some_and(|arg| arg.long.as_deref() == Some(name)) {
+                    return Err(self.display_version(path));
+                }
+
+                if self.man_arg().is_some_and(|arg| arg.long.as_deref() == Some(name)) {
+                    return Err(ArgError::new(ArgErrorKind::DisplayHelp, self.render_man_for(path)));
+                }
+
+                let arg = self.find_long(name, color)?;
+
+                if arg.takes_value {
+                    let value = match inline {
+                        Some(value) => value,
+                        None => next_value(&mut iter, arg, &format!("--{}", name), color)?,
+                    };
+
+                    record(&mut matches, arg, Some(value), ValueSource::CommandLine, color)?;
+                } else if inline.is_some() {
+                    return Err(ArgError::new(
+                        ArgErrorKind::UnexpectedValue,
+                        format!("unexpected value for flag {}", quoted(&arg.display_name(), color)),
+                    ));
+                } else {
+                    record(&mut matches, arg, None, ValueSource::CommandLine, color)?;
+                }
+
+                continue;
+            }
+
+            let looks_negative = is_negative_number(current) && position < positionals.len();
+
+            if !only_positional && current.starts_with('-') && current.len() > 1 && !looks_negative {
+                let chars: Vec<char> = current[1..].chars().collect();
+
+                for (i, short) in chars.iter().enumerate() {
+                    if self.help_arg().is_some_and(|arg| arg.short == Some(*short)) {
+                        return Err(self.display_help(path, color));
+                    }
+
+                    if self.version_arg().is_some_and(|arg| arg.short == Some(*short)) {
+                        return Err(self.display_version(path));
+                    }
+
+                    let arg = self.find_short(*short, color)?;
+
+                    if !arg.takes_value {
+                        record(&mut matches, arg, None, ValueSource::CommandLine, color)?;
+                        continue;
+                    }
+
+                    let rest: String = chars[i + 1..].iter().collect();
+
+                    let value = if rest.is_empty() {
+                        next_value(&mut iter, arg, &format!("-{}", short), color)?
+                    } else {
+                        rest.strip_prefix('=').unwrap_or(&rest).to_string()
+                    };
+
+                    record(&mut matches, arg, Some(value), ValueSource::CommandLine, color)?;
+                    break;
+                }
+
+                continue;
+            }
+
+            if !only_positional && current == "help" && !self.subcommands.is_empty() && self.find_subcommand("help").is_none() {
+                return Err(self.help_subcommand(&args[index + 1..], path, color));
+            }
+
+            if !only_positional {
+                if let Some(subcommand) = self.find_subcommand(current) {
+                    self.apply_fallbacks(&mut matches, color)?;
+
+                    let child = subcommand.inherit(self);
+                    let child_path = format!("{} {}", path, subcommand.name);
+                    let mut child_matches = child.parse(&args[index + 1..], &child_path, color)?;
+
+                    // Globals given at either level are visible from both
+                    for arg in self.args.iter().filter(|arg| arg.global) {
+                        let explicit = |m: &ArgMatches| m.source(&arg.name) == Some(ValueSource::CommandLine);
+
+                        if explicit(&child_matches) {
+                            matches.copy_from(&child_matches, &arg.name);
+                        } else if explicit(&matches) || !child_matches.contains(&arg.name) {
+                            child_matches.copy_from(&matches, &arg.name);
+                            child_matches.propagate(&arg.name);
+                        }
+                    }
+
+                    // Validated only now, so required globals may come after the subcommand
+                    self.validate(&matches, color)?;
+                    matches.subcommand = Some((subcommand.name.clone(), Box::new(child_matches)));
+
+                    return Ok(matches);
+                }
+            }
+
+            match positionals.get(position) {
+                Some(arg) => {
+                    record(&mut matches, arg, Some(current.clone()), ValueSource::CommandLine, color)?;
+
+                    if !arg.multiple {
+                        position += 1;
+                    }
+                }
+                None if self.allow_external && !only_positional => {
+                    let Some(external) = self.find_external(path, current) else {
+                        return Err(self.unknown_subcommand(current, color));
+                    };
+
+                    self.apply_fallbacks(&mut matches, color)?;
+                    self.validate(&matches, color)?;
+
+                    matches.external = Some(ExternalSubcommand {
+                        name: current.clone(),
+                        path: external,
+                        args: args[index + 1..].to_vec(),
+                    });
+
+                    return Ok(matches);
+                }
+                None if !self.subcommands.is_empty() && !only_positional => {
+                    return Err(self.unknown_subcommand(current, color));
+                }
+                None => {
+                    return Err(ArgError::new(
+                        ArgErrorKind::UnknownArgument,
+                        format!("unexpected argument {} found", quoted(current, color)),
+                    ));
+                }
+            }
+        }
+
+        self.apply_fallbacks(&mut matches, color)?;
+        self.validate(&matches, color)?;
+
+        if self.subcommand_required {
+            let names = self.subcommands
+                .iter()
+                .filter(|sub| !sub.hidden)
+                .map(|sub| sub.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            return Err(ArgError::new(
+                ArgErrorKind::MissingSubcommand,
+                format!("{} requires a subcommand\n  [subcommands: {}]", quoted(path, color), names),
+            ));
+        }
+
+        Ok(matches)
+    }
+
+    fn apply_fallbacks(&self, matches: &mut ArgMatches, color: ColorChoice) -> Result<(), ArgError> {
+        for arg in &self.args {
+            if matches.contains(&arg.name) {
+                continue;
+            }
+
+            if let Some(value) = arg.env.as_ref().and_then(|env| std::env::var(env).ok()) {
+                if arg.takes_value || arg.is_positional() {
+                    record(matches, arg, Some(value), ValueSource::Env, color)?;
+                } else if !matches!(value.to_lowercase().as_str(), "" | "0" | "false" | "no" | "off") {
+                    record(matches, arg, None, ValueSource::Env, color)?;
+                }
+
+                continue;
+            }
+
+            if let Some(default) = &arg.default {
+                record(matches, arg, Some(default.clone()), ValueSource::Default, color)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self, matches: &ArgMatches, color: ColorChoice) -> Result<(), ArgError> {
+        let missing: Vec<String> = self.args
+            .iter()
+            .filter(|arg| arg.required && !matches.contains(&arg.name) && !self.inherited.contains(&arg.name))
+            .map(|arg| arg.display_name())
+            .collect();
+
+        if !missing.is_empty() {
+            let list = missing.iter().map(|name| format!("  {}", quoted(name, color))).collect::<Vec<_>>().join("\n");
+
+            return Err(ArgError::new(
+                ArgErrorKind::MissingRequired,
+                format!("the following required arguments were not provided:\n{}", list),
+            ));
+        }
+
+        for arg in &self.args {
+            if matches.source(&arg.name) != Some(ValueSource::CommandLine) {
+                continue;
+            }
+
+            for other in &arg.conflicts {
+                if matches.source(other) == Some(ValueSource::CommandLine) {
+                    let other = self.args
+                        .iter()
+                        .find(|candidate| &candidate.name == other)
+                        .map(|candidate| candidate.display_name())
+                        .unwrap_or_else(|| other.clone());
+
+                    return Err(ArgError::new(
+                        ArgErrorKind::Conflict,
+                        format!("the argument {} cannot be used with {}", quoted(&arg.display_name(), color), quoted(&other, color)),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn record(matches: &mut ArgMatches, arg: &Arg, value: Option<String>, source: ValueSource, color: ColorChoice) -> Result<(), ArgError> {
+    if let Some(value) = &value {
+        if !arg.possible_values.is_empty() && !arg.possible_values.contains(value) {
+            return Err(ArgError::new(
+                ArgErrorKind::InvalidValue,
+                format!(
+                    "invalid value {} for {}\n  [possible values: {}]",
+                    quoted(value, color),
+                    quoted(&arg.display_name(), color),
+                    arg.possible_values.join(", "),
+                ),
+            ));
+        }
+
+        if let Some(validator) = arg.validator {
+            validator(value).map_err(|e| {
+                ArgError::new(
+                    ArgErrorKind::InvalidValue,
+                    format!("invalid value {} for {}: {}", quoted(value, color), quoted(&arg.display_name(), color), e),
+                )
+            })?;
+        }
+
+        // Later occurrences override earlier ones unless the arg collects values
+        if !arg.multiple {
+            matches.values.remove(&arg.name);
+        }
+    }
+
+    matches.push(&arg.name, value, source);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &std::path::Path) -> bool {
+    path.metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+// Without an executable bit, any regular file will do.
+#[cfg(not(unix))]
+fn is_executable(path: &std::path::Path) -> bool {
+    path.is_file()
+}
+
+// The next argument as the value of `option`, unless it is a long option itself: `--output --verbose`
+// is far more likely a forgotten value than an output file named `--verbose`.
+fn next_value<'a>(iter: &mut impl Iterator<Item = (usize, &'a String)>, arg: &Arg, option: &str, color: ColorChoice) -> Result<String, ArgError> {
+    let Some((_, value)) = iter.next() else {
+        return Err(missing_value(arg, color));
+    };
+
+    if value.starts_with("--") {
+        let mut error = missing_value(arg, color);
+        error.message.push_str(&format!(
+            "\n\n  {} to pass {} as the value, use {}",
+            paint("tip:", Style::new().fg(Color::Green), Stream::Stderr, color),
+            quoted(value, color),
+            quoted(&format!("{}={}", option, value), color),
+        ));
+
+        return Err(error);
+    }
+
+    Ok(value.clone())
+}
+
+fn missing_value(arg: &Arg, color: ColorChoice) -> ArgError {
+    ArgError::new(
+        ArgErrorKind::MissingValue,
+        format!("a value is required for {} but none was supplied", quoted(&arg.display_name(), color)),
+    )
+}
+
+fn unknown_argument(name: &str, color: ColorChoice) -> ArgError {
+    ArgError::new(
+        ArgErrorKind::UnknownArgument,
+        format!("unexpected argument {} found", quoted(name, color)),
+    )
+}
+
+// `-5`, `-0.5` or `-1e3`, but not `-inf` or `-nan`, which stay options.
+fn is_negative_number(arg: &str) -> bool {
+    let Some(rest) = arg.strip_prefix('-') else {
+        return false;
+    };
+
+    let (mantissa, exponent) = match rest.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (rest, None),
+    };
+
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    let mantissa_ok = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (digits(whole) || whole.is_empty()) && digits(fraction),
+        None => digits(mantissa),
+    };
+
+    let exponent_ok = match exponent {
+        Some(exponent) => digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent)),
+        None => true,
+    };
+
+    mantissa_ok && exponent_ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use broccolor::ColorLevel;
+
+    fn command() -> Command {
+        Command::new("tool")
+            .arg(Arg::new("verbose").short('v').long("verbose"))
+            .arg(Arg::new("value"))
+    }
+
+    fn parse(args: &[&str]) -> Result<ArgMatches, ArgError> {
+        command().try_get_matches_from(std::iter::once("tool").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn negative_numbers_are_positionals() {
+        for number in ["-5", "-0.5", "-.5", "-1e3", "-2.5E-4"] {
+            let matches = parse(&[number]).unwrap();
+            assert_eq!(matches.value_of("value"), Some(number), "{}", number);
+        }
+    }
+
+    #[test]
+    fn non_numeric_dashes_are_options() {
+        for arg in ["-inf", "-nan", "-infinity", "-1e", "-1."] {
+            assert!(parse(&[arg]).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn required_globals_may_follow_the_subcommand() {
+        let command = Command::new("tool")
+            .arg(Arg::new("token").long("token").takes_value(true).required(true).global(true))
+            .subcommand(Command::new("sub"));
+
+        for args in [["tool", "--token", "x", "sub"], ["tool", "sub", "--token", "x"]] {
+            let matches = command.try_get_matches_from(args).unwrap();
+
+            assert_eq!(matches.value_of("token"), Some("x"));
+            assert_eq!(matches.subcommand_matches("sub").unwrap().value_of("token"), Some("x"));
+        }
+
+        assert!(command.try_get_matches_from(["tool", "sub"]).is_err());
+    }
+
+    #[test]
+    fn option_values_cannot_be_long_options() {
+        let command = command().arg(Arg::new("output").short('o').long("output").takes_value(true));
+        let parse = |args: &[&str]| command.try_get_matches_from(std::iter::once("tool").chain(args.iter().copied()));
+
+        assert!(parse(&["--output", "--verbose"]).is_err());
+        assert!(parse(&["-o", "--verbose"]).is_err());
+        assert_eq!(parse(&["--output=--verbose"]).unwrap().value_of("output"), Some("--verbose"));
+        assert_eq!(parse(&["-o=--verbose"]).unwrap().value_of("output"), Some("--verbose"));
+        assert_eq!(parse(&["--output", "-"]).unwrap().value_of("output"), Some("-"));
+    }
+
+    #[test]
+    fn double_dash_ends_options() {
+        let matches = parse(&["--", "-v"]).unwrap();
+
+        assert_eq!(matches.value_of("value"), Some("-v"));
+        assert!(!matches.flag("verbose"));
+    }
+
+    #[test]
+    fn color_choice_applies_to_help_and_errors() {
+        let command = Command::new("tool").color_flag(true).arg(Arg::new("output").long("output").takes_value(true));
+
+        ColorLevel::set_override(Some(ColorLevel::TrueColor));
+        let help = command.try_get_matches_from(["tool", "--color", "never", "--help"]).unwrap_err();
+        let error = command.try_get_matches_from(["tool", "--color=never", "--bogus"]).unwrap_err();
+        let default = command.try_get_matches_from(["tool", "--bogus"]).unwrap_err();
+        let skipped = command.try_get_matches_from(["tool", "--", "--color", "never"]).unwrap_err();
+        ColorLevel::set_override(None);
+
+        assert!(help.is_display());
+        assert!(!help.message.contains('\x1b'), "{}", help.message);
+        assert!(!error.to_string().contains('\x1b'), "{}", error);
+        assert!(default.to_string().contains('\x1b'));
+        assert!(skipped.to_string().contains('\x1b'));
+    }
+}
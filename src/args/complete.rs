@@ -0,0 +1,604 @@
+use std::str::FromStr;
+use super::arg::Arg;
+use super::command::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            "powershell" | "pwsh" => Ok(Shell::PowerShell),
+            _ => Err(format!("Unknown shell: '{}'", s)),
+        }
+    }
+}
+
+// The argument the shell passes back to the binary in dynamic mode.
+pub const COMPLETE_ARG: &str = "__complete";
+
+// One command in the tree, flattened for script generation.
+struct Node {
+    key: String,
+    command: Command,
+    // (word, key of the child it leads to), aliases included
+    transitions: Vec<(String, String)>,
+}
+
+fn collect(command: &Command, key: String, nodes: &mut Vec<Node>) {
+    let mut transitions = Vec::new();
+
+    for sub in &command.subcommands {
+        let child_key = format!("{}__{}", key, sub.name);
+
+        for word in std::iter::once(&sub.name).chain(&sub.aliases) {
+            transitions.push((word.clone(), child_key.clone()));
+        }
+    }
+
+    nodes.push(Node {
+        key: key.clone(),
+        command: command.clone(),
+        transitions,
+    });
+
+    for sub in &command.subcommands {
+        collect(&sub.inherit(command), format!("{}__{}", key, sub.name), nodes);
+    }
+}
+
+fn flags(arg: &Arg) -> Vec<String> {
+    let mut flags = Vec::new();
+
+    if let Some(long) = &arg.long {
+        flags.push(format!("--{}", long));
+    }
+
+    if let Some(short) = arg.short {
+        flags.push(format!("-{}", short));
+    }
+
+    flags
+}
+
+// Named args including the built-in help, version and man page flags.
+fn options(command: &Command) -> Vec<Arg> {
+    command.args
+        .iter()
+        .filter(|arg| !arg.is_positional())
+        .cloned()
+        .chain(command.help_arg())
+        .chain(command.version_arg())
+        .chain(command.man_arg())
+        .collect()
+}
+
+// Flags whose next word is their value, which the subcommand walk has to step over.
+fn value_flags(command: &Command) -> Vec<String> {
+    options(command)
+        .iter()
+        .filter(|arg| arg.takes_value)
+        .flat_map(flags)
+        .collect()
+}
+
+// Bash breaks words at `=` by default, so `--color=al` arrives as `--color`, `=`, `al`.
+fn rejoin(words: &[String]) -> Vec<String> {
+    let mut joined: Vec<String> = Vec::new();
+    let mut glue = false;
+
+    for word in words {
+        match joined.last_mut() {
+            Some(last) if word == "=" && last.starts_with("--") && !last.contains('=') => {
+                last.push('=');
+                glue = true;
+            }
+            Some(last) if glue => {
+                last.push_str(word);
+                glue = false;
+            }
+            _ => joined.push(word.clone()),
+        }
+    }
+
+    joined
+}
+
+fn subcommand_words(command: &Command) -> Vec<(String, String)> {
+    let mut words: Vec<(String, String)> = command.subcommands
+        .iter()
+        .filter(|sub| !sub.hidden)
+        .map(|sub| (sub.name.clone(), sub.about.clone().unwrap_or_default()))
+        .collect();
+
+    if !words.is_empty() && command.find_subcommand("help").is_none() {
+        words.push(("help".to_string(), "Print help for a subcommand".to_string()));
+    }
+
+    words
+}
+
+fn single_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+fn fish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn powershell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn zsh_describe(word: &str, description: &str) -> String {
+    let word = word.replace(':', "\\:");
+    let description = description.replace('\n', " ");
+
+    single_quote(&format!("{}:{}", word, description))
+}
+
+impl Command {
+    // A standalone script with every flag and subcommand baked in.
+    pub fn generate_completion(&self, shell: Shell) -> String {
+        let mut nodes = Vec::new();
+        collect(self, self.name.clone(), &mut nodes);
+
+        match shell {
+            Shell::Bash => self.bash_script(&nodes),
+            Shell::Zsh => self.zsh_script(&nodes),
+            Shell::Fish => self.fish_script(&nodes),
+            Shell::PowerShell => self.powershell_script(&nodes),
+        }
+    }
+
+    // A script that asks the binary for candidates through `<bin> __complete <words>...`.
+    pub fn generate_dynamic_completion(&self, shell: Shell) -> String {
+        let name = &self.name;
+        let function = format!("_{}", name.replace('-', "_"));
+
+        match shell {
+            // Candidates come back as `--opt=value`, but bash only replaces the part after the `=`
+            Shell::Bash => format!(
+                "{function}() {{\n    local IFS=$'\\n'\n    COMPREPLY=($(\"${{COMP_WORDS[0]}}\" {arg} \"${{COMP_WORDS[@]:1:COMP_CWORD}}\" 2>/dev/null))\n    if [[ \"${{COMP_WORDS[COMP_CWORD]}}\" == \"=\" || \"${{COMP_WORDS[COMP_CWORD-1]}}\" == \"=\" ]]; then\n        COMPREPLY=(\"${{COMPREPLY[@]#*=}}\")\n    fi\n}}\n\ncomplete -o default -F {function} {name}\n",
+                arg = COMPLETE_ARG,
+            ),
+            Shell::Zsh => format!(
+                "#compdef {name}\n\n{function}() {{\n    local -a candidates\n    candidates=(\"${{(@f)$(\"${{words[1]}}\" {arg} \"${{(@)words[2,CURRENT]}}\" 2>/dev/null)}}\")\n    compadd -a candidates\n}}\n\ncompdef {function} {name}\n",
+                arg = COMPLETE_ARG,
+            ),
+            Shell::Fish => format!(
+                "complete -c {name} -f -a '({name} {arg} (commandline -opc)[2..-1] (commandline -ct) 2>/dev/null)'\n",
+                arg = COMPLETE_ARG,
+            ),
+            Shell::PowerShell => format!(
+                "Register-ArgumentCompleter -Native -CommandName {quoted} -ScriptBlock {{\n    param($wordToComplete, $commandAst, $cursorPosition)\n    $words = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object {{ $_.ToString() }})\n    if ($wordToComplete -eq '') {{ $words += '' }}\n    & {quoted} {arg} @words 2>$null | ForEach-Object {{\n        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)\n    }}\n}}\n",
+                quoted = powershell_quote(name),
+                arg = COMPLETE_ARG,
+            ),
+        }
+    }
+
+    // Candidates for the last word in `words`, which are the arguments after the binary name.
+    pub fn complete(&self, words: &[String]) -> Vec<String> {
+        let words = rejoin(words);
+
+        let (current, previous) = match words.split_last() {
+            Some((current, previous)) => (current.as_str(), previous),
+            None => ("", &[][..]),
+        };
+
+        let mut command = self.clone();
+        let mut pending: Option<Arg> = None;
+        let mut position = 0;
+
+        for word in previous {
+            if pending.take().is_some() {
+                continue;
+            }
+
+            if word.starts_with("--") && !word.contains('=') {
+                pending = command.args.iter().find(|arg| arg.long.as_deref() == Some(&word[2..]) && arg.takes_value).cloned();
+            } else if word.starts_with('-') && word.len() == 2 {
+                pending = command.args.iter().find(|arg| arg.short == word.chars().nth(1) && arg.takes_value).cloned();
+            } else if !word.starts_with('-') {
+                match command.find_subcommand(word) {
+                    Some(sub) => {
+                        command = sub.inherit(&command);
+                        position = 0;
+                    }
+                    None => position += 1,
+                }
+            }
+        }
+
+        let values = |arg: &Arg, prefix: &str| -> Vec<String> {
+            let candidates = match arg.completer {
+                Some(completer) => completer(prefix),
+                None => arg.possible_values.clone(),
+            };
+
+            candidates.into_iter().filter(|value| value.starts_with(prefix)).collect()
+        };
+
+        if let Some(arg) = pending {
+            return values(&arg, current);
+        }
+
+        if let Some((flag, value)) = current.strip_prefix("--").and_then(|rest| rest.split_once('=')) {
+            return match command.args.iter().find(|arg| arg.long.as_deref() == Some(flag)) {
+                Some(arg) => values(arg, value).into_iter().map(|v| format!("--{}={}", flag, v)).collect(),
+                None => Vec::new(),
+            };
+        }
+
+        if current.starts_with('-') {
+            return options(&command)
+                .iter()
+                .flat_map(flags)
+                .filter(|flag| flag.starts_with(current))
+                .collect();
+        }
+
+        let mut candidates: Vec<String> = subcommand_words(&command)
+            .into_iter()
+            .map(|(word, _)| word)
+            .filter(|word| word.starts_with(current))
+            .collect();
+
+        let positionals: Vec<&Arg> = command.args.iter().filter(|arg| arg.is_positional()).collect();
+        let positional = positionals.get(position).or_else(|| positionals.last().filter(|arg| arg.multiple));
+
+        if let Some(arg) = positional {
+            candidates.extend(values(arg, current));
+        }
+
+        candidates
+    }
+
+    // Answers a dynamic completion request and exits, if `args` is one.
+    pub(crate) fn handle_completion_request(&self, args: &[String]) {
+        if args.get(1).map(|arg| arg.as_str()) != Some(COMPLETE_ARG) || self.find_subcommand(COMPLETE_ARG).is_some() {
+            return;
+        }
+
+        for candidate in self.complete(&args[2..]) {
+            println!("{}", candidate);
+        }
+
+        std::process::exit(0);
+    }
+
+    fn bash_script(&self, nodes: &[Node]) -> String {
+        let function = format!("_{}", self.name.replace('-', "_"));
+        let mut out = String::new();
+
+        out.push_str(&format!("{}() {{\n", function));
+        out.push_str("    local cur prev cmd opts i\n");
+        out.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+        out.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+        out.push_str(&format!("    cmd={}\n\n", single_quote(&self.name)));
+
+        // The default COMP_WORDBREAKS splits `--opt=value` into `--opt`, `=` and `value`
+        out.push_str("    if [[ \"${cur}\" == \"=\" ]]; then\n        cur=\"\"\n");
+        out.push_str("    elif [[ \"${prev}\" == \"=\" ]]; then\n        prev=\"${COMP_WORDS[COMP_CWORD-2]}\"\n    fi\n\n");
+
+        out.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+        out.push_str("        case \"${cmd}__${COMP_WORDS[i]}\" in\n");
+
+        for node in nodes {
+            for (word, child) in &node.transitions {
+                out.push_str(&format!("            {})\n                cmd={} ;;\n", single_quote(&format!("{}__{}", node.key, word)), single_quote(child)));
+            }
+
+            let values = value_flags(&node.command);
+
+            if !values.is_empty() {
+                let patterns: Vec<String> = values.iter().map(|flag| single_quote(&format!("{}__{}", node.key, flag))).collect();
+                out.push_str(&format!("            {})\n                ((i += 1))\n                [[ \"${{COMP_WORDS[i]}}\" == \"=\" ]] && ((i += 1)) ;;\n", patterns.join("|")));
+            }
+        }
+
+        out.push_str("        esac\n    done\n\n    case \"${cmd}\" in\n");
+
+        for node in nodes {
+            let options = options(&node.command);
+
+            let words: Vec<String> = options
+                .iter()
+                .flat_map(flags)
+                .chain(subcommand_words(&node.command).into_iter().map(|(word, _)| word))
+                .collect();
+
+            out.push_str(&format!("        {})\n", single_quote(&node.key)));
+            out.push_str(&format!("            opts={}\n", single_quote(&words.join(" "))));
+            out.push_str("            case \"${prev}\" in\n");
+
+            for arg in options.iter().filter(|arg| arg.takes_value) {
+                let reply = if arg.possible_values.is_empty() {
+                    "COMPREPLY=($(compgen -f -- \"${cur}\"))".to_string()
+                } else {
+                    format!("COMPREPLY=($(compgen -W {} -- \"${{cur}}\"))", single_quote(&arg.possible_values.join(" ")))
+                };
+
+                out.push_str(&format!("                {})\n                    {}\n                    return 0 ;;\n", flags(arg).join("|"), reply));
+            }
+
+            out.push_str("            esac\n");
+            out.push_str("            COMPREPLY=($(compgen -W \"${opts}\" -- \"${cur}\"))\n");
+            out.push_str("            ;;\n");
+        }
+
+        out.push_str("    esac\n}\n\n");
+        out.push_str(&format!("complete -F {} -o bashdefault -o default {}\n", function, self.name));
+
+        out
+    }
+
+    fn zsh_script(&self, nodes: &[Node]) -> String {
+        let function = format!("_{}", self.name.replace('-', "_"));
+        let mut out = String::new();
+
+        out.push_str(&format!("#compdef {}\n\n{}() {{\n", self.name, function));
+        out.push_str(&format!("    local cmd={} i\n    local -a commands options\n\n", single_quote(&self.name)));
+        out.push_str("    for ((i = 2; i < CURRENT; i++)); do\n");
+        out.push_str("        case \"${cmd}__${words[i]}\" in\n");
+
+        for node in nodes {
+            for (word, child) in &node.transitions {
+                out.push_str(&format!("            {})\n                cmd={} ;;\n", single_quote(&format!("{}__{}", node.key, word)), single_quote(child)));
+            }
+
+            let values = value_flags(&node.command);
+
+            if !values.is_empty() {
+                let patterns: Vec<String> = values.iter().map(|flag| single_quote(&format!("{}__{}", node.key, flag))).collect();
+                out.push_str(&format!("            {})\n                ((i += 1)) ;;\n", patterns.join("|")));
+            }
+        }
+
+        out.push_str("        esac\n    done\n\n    case \"${cmd}\" in\n");
+
+        for node in nodes {
+            let options = options(&node.command);
+
+            out.push_str(&format!("        {})\n", single_quote(&node.key)));
+            out.push_str("            case \"${words[CURRENT-1]}\" in\n");
+
+            for arg in options.iter().filter(|arg| arg.takes_value) {
+                let reply = if arg.possible_values.is_empty() {
+                    "_files".to_string()
+                } else {
+                    format!("compadd -- {}", arg.possible_values.iter().map(|v| single_quote(v)).collect::<Vec<_>>().join(" "))
+                };
+
+                out.push_str(&format!("                {})\n                    {}\n                    return ;;\n", flags(arg).join("|"), reply));
+            }
+
+            out.push_str("            esac\n");
+
+            let commands: Vec<String> = subcommand_words(&node.command)
+                .iter()
+                .map(|(word, about)| zsh_describe(word, about))
+                .collect();
+
+            let descriptions: Vec<String> = options
+                .iter()
+                .flat_map(|arg| flags(arg).into_iter().map(|flag| zsh_describe(&flag, arg.help.as_deref().unwrap_or(""))).collect::<Vec<_>>())
+                .collect();
+
+            out.push_str(&format!("            commands=({})\n", commands.join(" ")));
+            out.push_str(&format!("            options=({})\n", descriptions.join(" ")));
+            out.push_str("            ;;\n");
+        }
+
+        out.push_str("    esac\n\n");
+        out.push_str("    if [[ \"${words[CURRENT]}\" == -* ]]; then\n");
+        out.push_str("        _describe -t options 'option' options\n");
+        out.push_str("    elif (( ${#commands} )); then\n");
+        out.push_str("        _describe -t commands 'command' commands\n");
+        out.push_str("    else\n        _files\n    fi\n}\n\n");
+        out.push_str(&format!("compdef {} {}\n", function, self.name));
+
+        out
+    }
+
+    fn fish_script(&self, nodes: &[Node]) -> String {
+        let function = format!("__{}_command", self.name.replace('-', "_"));
+        let mut out = String::new();
+
+        out.push_str(&format!("function {}\n", function));
+        out.push_str(&format!("    set -l cmd {}\n", fish_quote(&self.name)));
+        out.push_str("    set -l skip 0\n");
+        out.push_str("    for token in (commandline -opc)[2..-1]\n");
+        out.push_str("        if test $skip = 1\n            set skip 0\n            continue\n        end\n");
+        out.push_str("        switch \"$cmd\"__\"$token\"\n");
+
+        for node in nodes {
+            for (word, child) in &node.transitions {
+                out.push_str(&format!("            case {}\n                set cmd {}\n", fish_quote(&format!("{}__{}", node.key, word)), fish_quote(child)));
+            }
+
+            let values = value_flags(&node.command);
+
+            if !values.is_empty() {
+                let patterns: Vec<String> = values.iter().map(|flag| fish_quote(&format!("{}__{}", node.key, flag))).collect();
+                out.push_str(&format!("            case {}\n                set skip 1\n", patterns.join(" ")));
+            }
+        }
+
+        out.push_str("        end\n    end\n    echo $cmd\nend\n\n");
+
+        for node in nodes {
+            let condition = fish_quote(&format!("test ({}) = {}", function, node.key));
+
+            for (word, about) in subcommand_words(&node.command) {
+                out.push_str(&format!("complete -c {} -f -n {} -a {} -d {}\n", self.name, condition, fish_quote(&word), fish_quote(&about)));
+            }
+
+            for arg in options(&node.command) {
+                let mut line = format!("complete -c {} -n {}", self.name, condition);
+
+                if let Some(short) = arg.short {
+                    line.push_str(&format!(" -s {}", short));
+                }
+
+                if let Some(long) = &arg.long {
+                    line.push_str(&format!(" -l {}", long));
+                }
+
+                if arg.takes_value {
+                    line.push_str(" -r");
+                }
+
+                if !arg.possible_values.is_empty() {
+                    line.push_str(&format!(" -f -a {}", fish_quote(&arg.possible_values.join(" "))));
+                }
+
+                if let Some(help) = &arg.help {
+                    line.push_str(&format!(" -d {}", fish_quote(help)));
+                }
+
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+
+        out
+    }
+
+    fn powershell_script(&self, nodes: &[Node]) -> String {
+        let mut out = String::new();
+
+        out.push_str("using namespace System.Management.Automation\n\n");
+        out.push_str(&format!("Register-ArgumentCompleter -Native -CommandName {} -ScriptBlock {{\n", powershell_quote(&self.name)));
+        out.push_str("    param($wordToComplete, $commandAst, $cursorPosition)\n\n");
+        out.push_str(&format!("    $cmd = {}\n", powershell_quote(&self.name)));
+        out.push_str("    $skip = $false\n");
+        out.push_str("    foreach ($element in $commandAst.CommandElements | Select-Object -Skip 1) {\n");
+        out.push_str("        $word = $element.ToString()\n");
+        out.push_str("        if ($element.Extent.StartOffset -ge $cursorPosition) { break }\n");
+        out.push_str("        if ($skip) { $skip = $false; continue }\n");
+        out.push_str("        switch (\"${cmd}__${word}\") {\n");
+
+        for node in nodes {
+            for (word, child) in &node.transitions {
+                out.push_str(&format!("            {} {{ $cmd = {} }}\n", powershell_quote(&format!("{}__{}", node.key, word)), powershell_quote(child)));
+            }
+
+            for flag in value_flags(&node.command) {
+                out.push_str(&format!("            {} {{ $skip = $true }}\n", powershell_quote(&format!("{}__{}", node.key, flag))));
+            }
+        }
+
+        out.push_str("        }\n    }\n\n");
+        out.push_str("    $completions = @(switch ($cmd) {\n");
+
+        for node in nodes {
+            out.push_str(&format!("        {} {{\n", powershell_quote(&node.key)));
+
+            for arg in options(&node.command) {
+                let help = arg.help.clone().unwrap_or_else(|| arg.name.clone());
+
+                for flag in flags(&arg) {
+                    out.push_str(&format!(
+                        "            [CompletionResult]::new({flag}, {flag}, [CompletionResultType]::ParameterName, {help})\n",
+                        flag = powershell_quote(&flag),
+                        help = powershell_quote(&help),
+                    ));
+                }
+            }
+
+            for (word, about) in subcommand_words(&node.command) {
+                let about = if about.is_empty() { word.clone() } else { about };
+
+                out.push_str(&format!(
+                    "            [CompletionResult]::new({word}, {word}, [CompletionResultType]::ParameterValue, {about})\n",
+                    word = powershell_quote(&word),
+                    about = powershell_quote(&about),
+                ));
+            }
+
+            out.push_str("            break\n        }\n");
+        }
+
+        out.push_str("    })\n\n");
+        out.push_str("    $completions | Where-Object { $_.CompletionText -like \"$wordToComplete*\" } |\n");
+        out.push_str("        Sort-Object -Property ListItemText\n}\n");
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command() -> Command {
+        Command::new("tool")
+            .arg(Arg::new("color").long("color").takes_value(true).possible_values(&["auto", "always", "never"]))
+            .arg(Arg::new("config").short('c').long("config").takes_value(true))
+            .arg(Arg::new("verbose").short('v').long("verbose"))
+            .subcommand(Command::new("build").about("Build it").arg(Arg::new("target").possible_values(&["debug", "release"])))
+            .subcommand(Command::new("bench"))
+    }
+
+    fn complete(words: &[&str]) -> Vec<String> {
+        command().complete(&words.iter().map(|word| word.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn completes_subcommands_options_and_values() {
+        assert_eq!(complete(&["b"]), ["build", "bench"]);
+        assert_eq!(complete(&["--co"]), ["--color", "--config"]);
+        assert_eq!(complete(&["--color", "a"]), ["auto", "always"]);
+        assert_eq!(complete(&["build", "r"]), ["release"]);
+    }
+
+    #[test]
+    fn completes_values_split_at_equals() {
+        assert_eq!(complete(&["--color=al"]), ["--color=always"]);
+        assert_eq!(complete(&["--color", "=", "al"]), ["--color=always"]);
+        assert_eq!(complete(&["--color", "="]), ["--color=auto", "--color=always", "--color=never"]);
+    }
+
+    #[test]
+    fn option_values_are_not_subcommands() {
+        assert_eq!(complete(&["--config", "build", "r"]), Vec::<String>::new());
+        assert_eq!(complete(&["-c", "build", "bu"]), ["build"]);
+        assert_eq!(complete(&["--config", "=", "build", "bu"]), ["build"]);
+    }
+
+    #[test]
+    fn static_scripts_step_over_option_values() {
+        let bash = command().generate_completion(Shell::Bash);
+        assert!(bash.contains("'tool__--color'|'tool__--config'|'tool__-c')\n                ((i += 1))"), "{}", bash);
+        assert!(bash.contains("prev=\"${COMP_WORDS[COMP_CWORD-2]}\""));
+
+        let zsh = command().generate_completion(Shell::Zsh);
+        assert!(zsh.contains("'tool__--color'|'tool__--config'|'tool__-c')\n                ((i += 1)) ;;"), "{}", zsh);
+
+        let fish = command().generate_completion(Shell::Fish);
+        assert!(fish.contains("case 'tool__--color' 'tool__--config' 'tool__-c'\n                set skip 1"), "{}", fish);
+
+        let powershell = command().generate_completion(Shell::PowerShell);
+        assert!(powershell.contains("'tool__-c' { $skip = $true }"), "{}", powershell);
+    }
+
+    #[test]
+    fn dynamic_bash_script_trims_before_equals() {
+        let bash = command().generate_dynamic_completion(Shell::Bash);
+
+        assert!(bash.contains("COMPREPLY=(\"${COMPREPLY[@]#*=}\")"), "{}", bash);
+        assert!(bash.ends_with("complete -o default -F _tool tool\n"));
+    }
+}
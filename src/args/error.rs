@@ -0,0 +1,100 @@
+use broccolor::{Color, ColorChoice, Stream, Style};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgErrorKind {
+    UnknownArgument,
+    UnknownSubcommand,
+    MissingSubcommand,
+    UnexpectedValue,
+    MissingValue,
+    MissingRequired,
+    InvalidValue,
+    Conflict,
+    DisplayHelp,
+    DisplayVersion,
+}
+
+#[derive(Clone)]
+pub struct ArgError {
+    pub kind: ArgErrorKind,
+    pub message: String,
+    pub usage: Option<String>,
+    // The `--color` choice given on the command line, which `Display` follows too
+    pub(crate) color: ColorChoice,
+}
+
+impl ArgError {
+    pub fn new(kind: ArgErrorKind, message: String) -> Self {
+        Self {
+            kind,
+            message,
+            usage: None,
+            color: ColorChoice::Auto,
+        }
+    }
+
+    pub(crate) fn with_usage(mut self, usage: String) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    pub(crate) fn with_color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    // Help and version requests travel as errors so parsing stops, but aren't failures.
+    pub fn is_display(&self) -> bool {
+        matches!(self.kind, ArgErrorKind::DisplayHelp | ArgErrorKind::DisplayVersion)
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        if self.is_display() {
+            0
+        } else {
+            2
+        }
+    }
+
+    pub fn exit(&self) -> ! {
+        if self.is_display() {
+            print!("{}", self);
+        } else {
+            eprintln!("{}", self);
+        }
+
+        std::process::exit(self.exit_code())
+    }
+}
+
+// Styles `text` for what `stream` supports under `color`; errors go to stderr, help and version to stdout.
+pub(crate) fn paint(text: &str, style: Style, stream: Stream, color: ColorChoice) -> String {
+    style.paint_for(text, color.level(stream))
+}
+
+// Highlights an argument or value inside an error message.
+pub(crate) fn quoted(text: &str, color: ColorChoice) -> String {
+    paint(&format!("'{}'", text), Style::new().fg(Color::Yellow), Stream::Stderr, color)
+}
+
+impl core::fmt::Display for ArgError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_display() {
+            return write!(f, "{}", self.message);
+        }
+
+        write!(f, "{} {}", paint("error:", Style::new().fg(Color::Red).bold(), Stream::Stderr, self.color), self.message)?;
+
+        if let Some(usage) = &self.usage {
+            write!(f, "\n\n{} {}", paint("Usage:", Style::new().bold().underline(), Stream::Stderr, self.color), usage)?;
+        }
+
+        write!(f, "\n\nFor more information, try '{}'.", paint("--help", Style::new().bold(), Stream::Stderr, self.color))
+    }
+}
+
+impl core::fmt::Debug for ArgError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}: {}", self.kind, self.message)
+    }
+}
@@ -0,0 +1,383 @@
+use broccolor::{Color, ColorLevel, Stream, Style};
+use crate::terminal::get_terminal_size;
+use crate::widgets::visible_len;
+use super::arg::Arg;
+use super::command::Command;
+
+#[derive(Debug, Clone, Copy)]
+pub struct HelpTheme {
+    pub header: Color,
+    pub flag: Color,
+    pub placeholder: Color,
+    pub description: Color,
+    pub muted: Color,
+}
+
+impl Default for HelpTheme {
+    fn default() -> Self {
+        Self {
+            header: Color::BrightGreen,
+            flag: Color::BrightCyan,
+            placeholder: Color::Cyan,
+            description: Color::Transparent,
+            muted: Color::BrightBlack,
+        }
+    }
+}
+
+impl HelpTheme {
+    pub fn plain() -> Self {
+        Self {
+            header: Color::Transparent,
+            flag: Color::Transparent,
+            placeholder: Color::Transparent,
+            description: Color::Transparent,
+            muted: Color::Transparent,
+        }
+    }
+
+    fn header(&self, text: &str, level: ColorLevel) -> String {
+        let style = match self.header {
+            Color::Transparent => Style::new(),
+            color => Style::new().fg(color),
+        };
+
+        style.bold().paint_for(text, level)
+    }
+}
+
+fn paint_color(text: &str, color: Color, level: ColorLevel) -> String {
+    if text.is_empty() || color == Color::Transparent {
+        text.to_string()
+    } else {
+        Style::new().fg(color).paint_for(text, level)
+    }
+}
+
+// A help entry before coloring, so column widths are measured on plain text.
+struct Row {
+    flags: String,
+    placeholder: String,
+    description: String,
+    // `[default: …]` and the like, shown muted after the description
+    notes: String,
+}
+
+impl Row {
+    fn width(&self) -> usize {
+        self.flags.chars().count() + self.placeholder.chars().count()
+    }
+}
+
+fn arg_row(arg: &Arg) -> Row {
+    let flags = if arg.is_positional() {
+        String::new()
+    } else {
+        match (arg.short, &arg.long) {
+            (Some(short), Some(long)) => format!("-{}, --{}", short, long),
+            (Some(short), None) => format!("-{}", short),
+            (None, Some(long)) => format!("    --{}", long),
+            (None, None) => String::new(),
+        }
+    };
+
+    let dots = if arg.multiple { "..." } else { "" };
+
+    let placeholder = if arg.is_positional() {
+        format!("<{}>{}", arg.get_value_name(), dots)
+    } else if arg.takes_value {
+        format!(" <{}>{}", arg.get_value_name(), dots)
+    } else {
+        String::new()
+    };
+
+    let description = arg.help.clone().unwrap_or_default();
+    let mut notes = Vec::new();
+
+    if let Some(default) = &arg.default {
+        notes.push(format!("[default: {}]", default));
+    }
+
+    if let Some(env) = &arg.env {
+        notes.push(format!("[env: {}]", env));
+    }
+
+    if !arg.possible_values.is_empty() {
+        notes.push(format!("[possible values: {}]", arg.possible_values.join(", ")));
+    }
+
+    Row { flags, placeholder, description, notes: notes.join(" ") }
+}
+
+pub(crate) fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(10);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for word in text.split_whitespace() {
+        let word_width = visible_len(word);
+
+        if line_width > 0 && line_width + 1 + word_width > width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+
+        if line_width > 0 {
+            line.push(' ');
+            line_width += 1;
+        }
+
+        line.push_str(word);
+        line_width += word_width;
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn render_section(out: &mut String, title: &str, rows: &[Row], theme: &HelpTheme, width: usize, level: ColorLevel) {
+    if rows.is_empty() {
+        return;
+    }
+
+    out.push_str(&format!("\n{}\n", theme.header(title, level)));
+
+    const INDENT: usize = 2;
+    const GAP: usize = 2;
+
+    // Descriptions move below the flags when the left column would take too much space
+    let column = rows.iter().map(Row::width).max().unwrap_or(0);
+    let inline = column + INDENT + GAP <= width / 2;
+    let description_start = if inline { INDENT + column + GAP } else { INDENT + 8 };
+    let description_width = width.saturating_sub(description_start);
+
+    for row in rows {
+        let left = format!(
+            "{}{}",
+            paint_color(&row.flags, theme.flag, level),
+            paint_color(&row.placeholder, theme.placeholder, level),
+        );
+
+        out.push_str(&" ".repeat(INDENT));
+        out.push_str(&left);
+
+        let text = [row.description.as_str(), row.notes.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if text.is_empty() {
+            out.push('\n');
+            continue;
+        }
+
+        // Wrapped as plain text and colored line by line, so no color runs into the indent
+        let lines = wrap_text(&text, description_width);
+        let mut description_words = row.description.split_whitespace().count();
+
+        for (i, line) in lines.iter().enumerate() {
+            let words = line.split(' ').count();
+            let split = description_words.min(words);
+            description_words -= split;
+
+            let (description, notes) = match split {
+                0 => ("", line.as_str()),
+                _ => line
+                    .match_indices(' ')
+                    .nth(split - 1)
+                    .map_or((line.as_str(), ""), |(at, _)| (&line[..at], &line[at + 1..])),
+            };
+
+            let line = match (description.is_empty(), notes.is_empty()) {
+                (false, false) => format!(
+                    "{} {}",
+                    paint_color(description, theme.description, level),
+                    paint_color(notes, theme.muted, level),
+                ),
+                (true, _) => paint_color(notes, theme.muted, level),
+                (_, true) => paint_color(description, theme.description, level),
+            };
+
+            if i == 0 && inline {
+                out.push_str(&" ".repeat(column - row.width() + GAP));
+            } else {
+                out.push('\n');
+                out.push_str(&" ".repeat(description_start));
+            }
+
+            out.push_str(&line);
+        }
+
+        out.push('\n');
+    }
+}
+
+impl Command {
+    pub fn render_help(&self) -> String {
+        self.render_help_for(&self.name, ColorLevel::detect(Stream::Stdout))
+    }
+
+    pub fn print_help(&self) {
+        print!("{}", self.render_help());
+    }
+
+    pub(crate) fn render_help_for(&self, path: &str, level: ColorLevel) -> String {
+        let theme = self.help_theme.unwrap_or_default();
+        let width = get_terminal_size().map(|(cols, _)| cols as usize).unwrap_or(80).clamp(40, 120);
+        let mut out = String::new();
+
+        if let Some(about) = &self.about {
+            for line in wrap_text(about, width) {
+                out.push_str(&line);
+                out.push('\n');
+            }
+
+            out.push('\n');
+        }
+
+        out.push_str(&format!("{} {}\n", theme.header("Usage:", level), self.usage_for(path)));
+
+        let mut commands: Vec<Row> = self.subcommands
+            .iter()
+            .filter(|sub| !sub.hidden)
+            .map(|sub| Row {
+                flags: std::iter::once(&sub.name).chain(&sub.aliases).cloned().collect::<Vec<_>>().join(", "),
+                placeholder: String::new(),
+                description: sub.about.clone().unwrap_or_default(),
+                notes: String::new(),
+            })
+            .collect();
+
+        if !commands.is_empty() && self.find_subcommand("help").is_none() {
+            commands.push(Row {
+                flags: "help".to_string(),
+                placeholder: String::new(),
+                description: "Print this message or the help of the given subcommand(s)".to_string(),
+                notes: String::new(),
+            });
+        }
+
+        let arguments: Vec<Row> = self.args
+            .iter()
+            .filter(|arg| arg.is_positional())
+            .map(arg_row)
+            .collect();
+
+        let mut options: Vec<Row> = self.args
+            .iter()
+            .filter(|arg| !arg.is_positional())
+            .map(arg_row)
+            .collect();
+
+        if let Some(help) = self.help_arg() {
+            options.push(arg_row(&help));
+        }
+
+        if let Some(version) = self.version_arg() {
+            options.push(arg_row(&version));
+        }
+
+        if let Some(man) = self.man_arg() {
+            options.push(arg_row(&man));
+        }
+
+        render_section(&mut out, "Commands:", &commands, &theme, width, level);
+        render_section(&mut out, "Arguments:", &arguments, &theme, width, level);
+        render_section(&mut out, "Options:", &options, &theme, width, level);
+
+        out
+    }
+
+    // The built-in `-h, --help` flag, unless the command defines its own.
+    pub(crate) fn help_arg(&self) -> Option<Arg> {
+        let mut arg = Arg::new("help").help("Print help");
+
+        if !self.args.iter().any(|a| a.long.as_deref() == Some("help")) {
+            arg = arg.long("help");
+        }
+
+        if !self.args.iter().any(|a| a.short == Some('h')) {
+            arg = arg.short('h');
+        }
+
+        (!arg.is_positional()).then_some(arg)
+    }
+
+    // The built-in `-V, --version` flag when a version is set.
+    pub(crate) fn version_arg(&self) -> Option<Arg> {
+        self.version.as_ref()?;
+
+        let mut arg = Arg::new("version").help("Print version");
+
+        if !self.args.iter().any(|a| a.long.as_deref() == Some("version")) {
+            arg = arg.long("version");
+        }
+
+        if !self.args.iter().any(|a| a.short == Some('V')) {
+            arg = arg.short('V');
+        }
+
+        (!arg.is_positional()).then_some(arg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_text_breaks_between_words() {
+        assert_eq!(wrap_text("the quick brown fox jumps", 10), ["the quick", "brown fox", "jumps"]);
+        assert_eq!(wrap_text("", 20), [""]);
+        assert_eq!(wrap_text("an extraordinarily long word", 10), ["an", "extraordinarily", "long word"]);
+    }
+
+    #[test]
+    fn wrap_text_ignores_escapes_when_measuring() {
+        assert_eq!(wrap_text("\x1b[1maaaaa\x1b[0m bbbb", 10), ["\x1b[1maaaaa\x1b[0m bbbb"]);
+    }
+
+    #[test]
+    fn help_lists_commands_arguments_and_options() {
+        let help = Command::new("tool")
+            .about("Does things")
+            .version("1.0")
+            .arg(Arg::new("input").help("File to read"))
+            .arg(Arg::new("jobs").short('j').long("jobs").takes_value(true).default_value("4").help("Parallel jobs"))
+            .subcommand(Command::new("run").about("Run it"))
+            .render_help_for("tool", ColorLevel::None);
+
+        assert!(help.starts_with("Does things\n\nUsage: tool [OPTIONS] [<INPUT>] [COMMAND]\n"), "{}", help);
+        assert!(help.contains("\nCommands:\n  run "), "{}", help);
+        assert!(help.contains("\nArguments:\n  <INPUT>  File to read\n"), "{}", help);
+        assert!(help.contains("-j, --jobs <JOBS>"), "{}", help);
+        assert!(help.contains("Parallel jobs [default: 4]"), "{}", help);
+        assert!(help.contains("-V, --version"), "{}", help);
+    }
+
+    #[test]
+    fn muted_notes_are_colored_per_line() {
+        let theme = HelpTheme { muted: Color::Blue, description: Color::Transparent, ..HelpTheme::default() };
+        let row = Row {
+            flags: "--color".to_string(),
+            placeholder: String::new(),
+            description: "When to use colors".to_string(),
+            notes: "[possible values: auto, always, never]".to_string(),
+        };
+
+        let mut out = String::new();
+        render_section(&mut out, "Options:", &[row], &theme, 40, ColorLevel::Ansi16);
+
+        let lines: Vec<&str> = out.lines().skip(2).collect();
+
+        assert_eq!(lines, [
+            "  \x1b[96m--color\x1b[39m  When to use colors \x1b[34m[possible\x1b[39m",
+            "           \x1b[34mvalues: auto, always, never]\x1b[39m",
+        ]);
+    }
+}
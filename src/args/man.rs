@@ -0,0 +1,325 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use super::arg::Arg;
+use super::command::Command;
+
+// The flag that prints the man page when enabled with `Command::man_flag`.
+pub const MAN_ARG: &str = "--generate-man";
+
+// Escapes text for roff so backslashes, dashes and leading control characters print literally.
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn bold(text: &str) -> String {
+    format!("\\fB{}\\fR", escape(text))
+}
+
+fn italic(text: &str) -> String {
+    format!("\\fI{}\\fR", escape(text))
+}
+
+// A quoted macro argument, where a double quote has to be written as `\(dq`.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", escape(text).replace('"', "\\(dq"))
+}
+
+// Blank lines in help text become roff paragraph breaks; `break_with` is `.PP` at the
+// top level and `.IP` inside a `.TP` entry so later paragraphs keep the entry's indent.
+fn paragraphs(text: &str, break_with: &str) -> String {
+    text.split("\n\n")
+        .map(|paragraph| escape(paragraph.trim()))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join(&format!("\n{}\n", break_with))
+}
+
+fn page_name(path: &str) -> String {
+    path.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+fn arg_entry(arg: &Arg) -> String {
+    let mut out = String::from(".TP\n");
+
+    let mut names = Vec::new();
+
+    if let Some(short) = arg.short {
+        names.push(bold(&format!("-{}", short)));
+    }
+
+    if let Some(long) = &arg.long {
+        names.push(bold(&format!("--{}", long)));
+    }
+
+    out.push_str(&names.join(", "));
+
+    if arg.is_positional() || arg.takes_value {
+        let dots = if arg.multiple { "..." } else { "" };
+
+        if !names.is_empty() {
+            out.push(' ');
+        }
+
+        out.push_str(&italic(&format!("<{}>{}", arg.get_value_name(), dots)));
+    }
+
+    out.push('\n');
+
+    let mut body = Vec::new();
+
+    if let Some(help) = &arg.help {
+        body.push(paragraphs(help, ".IP"));
+    }
+
+    if let Some(default) = &arg.default {
+        body.push(format!("Default: {}", italic(default)));
+    }
+
+    if let Some(env) = &arg.env {
+        body.push(format!("Environment: {}", bold(env)));
+    }
+
+    if !arg.possible_values.is_empty() {
+        let values: Vec<String> = arg.possible_values.iter().map(|value| italic(value)).collect();
+        body.push(format!("Possible values: {}", values.join(", ")));
+    }
+
+    out.push_str(&body.join("\n.br\n"));
+
+    if !body.is_empty() {
+        out.push('\n');
+    }
+
+    out
+}
+
+impl Command {
+    // A man(7) page for this command, as written by `write_man_pages`.
+    pub fn render_man(&self) -> String {
+        self.render_man_for(&self.name)
+    }
+
+    pub(crate) fn render_man_for(&self, path: &str) -> String {
+        let name = page_name(path);
+        let mut out = String::new();
+
+        let source = match &self.version {
+            Some(version) => format!("{} {}", name, version),
+            None => name.clone(),
+        };
+
+        out.push_str(&format!(".TH {} \"1\" \"\" {} \"User Commands\"\n", quote(&name.to_uppercase()), quote(&source)));
+
+        out.push_str(".SH NAME\n");
+        out.push_str(&escape(&name));
+
+        if let Some(summary) = self.about.as_ref().and_then(|about| about.lines().next()) {
+            out.push_str(&format!(" \\- {}", escape(summary.trim())));
+        }
+
+        out.push('\n');
+
+        let usage = self.usage_for(path);
+        let rest = usage.strip_prefix(path).unwrap_or("").trim();
+        out.push_str(".SH SYNOPSIS\n");
+        out.push_str(&bold(path));
+
+        if !rest.is_empty() {
+            out.push(' ');
+            out.push_str(&escape(rest));
+        }
+
+        out.push('\n');
+
+        if let Some(about) = &self.about {
+            out.push_str(".SH DESCRIPTION\n");
+            out.push_str(&paragraphs(about, ".PP"));
+            out.push('\n');
+        }
+
+        let options: Vec<Arg> = self.args
+            .iter()
+            .filter(|arg| !arg.is_positional())
+            .cloned()
+            .chain(self.help_arg())
+            .chain(self.version_arg())
+            .chain(self.man_arg())
+            .collect();
+
+        out.push_str(".SH OPTIONS\n");
+
+        for arg in &options {
+            out.push_str(&arg_entry(arg));
+        }
+
+        let arguments: Vec<&Arg> = self.args.iter().filter(|arg| arg.is_positional()).collect();
+
+        if !arguments.is_empty() {
+            out.push_str(".SH ARGUMENTS\n");
+
+            for arg in arguments {
+                out.push_str(&arg_entry(arg));
+            }
+        }
+
+        let subcommands: Vec<&Command> = self.subcommands.iter().filter(|sub| !sub.hidden).collect();
+
+        if !subcommands.is_empty() {
+            out.push_str(".SH COMMANDS\n");
+
+            for sub in &subcommands {
+                out.push_str(".TP\n");
+                out.push_str(&bold(&page_name(&format!("{} {}", path, sub.name))));
+                out.push('\n');
+
+                if let Some(about) = &sub.about {
+                    out.push_str(&paragraphs(about, ".IP"));
+                    out.push('\n');
+                }
+            }
+        }
+
+        let environment: Vec<&Arg> = self.args.iter().filter(|arg| arg.env.is_some()).collect();
+
+        if !environment.is_empty() {
+            out.push_str(".SH ENVIRONMENT\n");
+
+            for arg in environment {
+                out.push_str(".TP\n");
+                out.push_str(&bold(arg.env.as_deref().unwrap_or_default()));
+                out.push('\n');
+                out.push_str(&format!("Sets {}.\n", bold(&arg.display_name())));
+            }
+        }
+
+        if !self.examples.is_empty() {
+            out.push_str(".SH EXAMPLES\n");
+
+            for (command, description) in &self.examples {
+                out.push_str(".TP\n");
+                out.push_str(&bold(command));
+                out.push('\n');
+
+                if !description.is_empty() {
+                    out.push_str(&paragraphs(description, ".IP"));
+                    out.push('\n');
+                }
+            }
+        }
+
+        if let Some(version) = &self.version {
+            out.push_str(".SH VERSION\n");
+            out.push_str(&escape(version));
+            out.push('\n');
+        }
+
+        if !subcommands.is_empty() {
+            let pages: Vec<String> = subcommands
+                .iter()
+                .map(|sub| format!("{}(1)", bold(&page_name(&format!("{} {}", path, sub.name)))))
+                .collect();
+
+            out.push_str(".SH SEE ALSO\n");
+            out.push_str(&pages.join(",\n"));
+            out.push('\n');
+        }
+
+        out
+    }
+
+    // Writes `<name>.1` plus one page per visible subcommand, e.g. `tool-db-migrate.1`,
+    // returning the written paths. Meant for build scripts and packaging.
+    pub fn write_man_pages(&self, dir: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+        std::fs::create_dir_all(dir.as_ref())?;
+        self.write_man_pages_for(dir.as_ref(), &self.name, &mut written)?;
+
+        Ok(written)
+    }
+
+    fn write_man_pages_for(&self, dir: &Path, path: &str, written: &mut Vec<PathBuf>) -> io::Result<()> {
+        let file = dir.join(format!("{}.1", page_name(path)));
+        std::fs::write(&file, self.render_man_for(path))?;
+        written.push(file);
+
+        for sub in self.subcommands.iter().filter(|sub| !sub.hidden) {
+            sub.inherit(self).write_man_pages_for(dir, &format!("{} {}", path, sub.name), written)?;
+        }
+
+        Ok(())
+    }
+
+    // The `--generate-man` flag when enabled, unless the command defines its own.
+    pub(crate) fn man_arg(&self) -> Option<Arg> {
+        let long = MAN_ARG.strip_prefix("--")?;
+
+        if !self.man_flag || self.args.iter().any(|arg| arg.long.as_deref() == Some(long)) {
+            return None;
+        }
+
+        Some(Arg::new(long).long(long).help("Print the man page"))
+    }
+
+    pub(crate) fn handle_man_request(&self, args: &[String]) {
+        if !self.man_flag || args.get(1).map(|arg| arg.as_str()) != Some(MAN_ARG) {
+            return;
+        }
+
+        print!("{}", self.render_man());
+        std::process::exit(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_dashes_backslashes_and_control_lines() {
+        assert_eq!(escape("--dry-run"), "\\-\\-dry\\-run");
+        assert_eq!(escape("C:\\path"), "C:\\epath");
+        assert_eq!(escape(".hidden\n'quoted"), "\\&.hidden\n\\&'quoted");
+    }
+
+    #[test]
+    fn title_quotes_are_escaped() {
+        let page = Command::new("say\"hi").version("1.0 \"beta\"").render_man();
+
+        assert!(page.starts_with(".TH \"SAY\\(dqHI\" \"1\" \"\" \"say\\(dqhi 1.0 \\(dqbeta\\(dq\" \"User Commands\"\n"), "{}", page);
+    }
+
+    #[test]
+    fn entry_paragraphs_keep_their_indent() {
+        let page = Command::new("tool")
+            .about("First.\n\nSecond.")
+            .arg(Arg::new("jobs").long("jobs").takes_value(true).help("How many.\n\nDefaults to the CPU count."))
+            .render_man();
+
+        assert!(page.contains(".SH DESCRIPTION\nFirst.\n.PP\nSecond.\n"), "{}", page);
+        assert!(page.contains(".TP\n\\fB\\-\\-jobs\\fR \\fI<JOBS>\\fR\nHow many.\n.IP\nDefaults to the CPU count.\n"), "{}", page);
+    }
+
+    #[test]
+    fn man_flag_is_documented_and_parsed() {
+        let command = Command::new("tool").man_flag(true);
+
+        assert!(command.render_man().contains("\\fB\\-\\-generate\\-man\\fR\nPrint the man page\n"));
+        assert!(command.render_help().contains("--generate-man"));
+        assert!(!Command::new("tool").render_help().contains("--generate-man"));
+
+        let error = command.try_get_matches_from(["tool", "--generate-man"]).unwrap_err();
+        assert!(error.is_display());
+        assert!(error.message.starts_with(".TH \"TOOL\""));
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::str::FromStr;
+use broccolor::ColorChoice;
+use super::error::{quoted, ArgError, ArgErrorKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueSource {
+    CommandLine,
+    Env,
+    Default,
+}
+
+// A subcommand that isn't defined but was found on `PATH` as `<bin>-<name>`.
+#[derive(Debug, Clone)]
+pub struct ExternalSubcommand {
+    pub name: String,
+    pub path: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl ExternalSubcommand {
+    pub fn run(&self) -> std::io::Result<ExitStatus> {
+        std::process::Command::new(&self.path).args(&self.args).status()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ArgMatches {
+    pub(crate) values: HashMap<String, Vec<String>>,
+    pub(crate) occurrences: HashMap<String, usize>,
+    pub(crate) sources: HashMap<String, ValueSource>,
+    pub(crate) subcommand: Option<(String, Box<ArgMatches>)>,
+    pub(crate) external: Option<ExternalSubcommand>,
+}
+
+impl ArgMatches {
+    // Copies the values of `name` from `other`, replacing any present here.
+    pub(crate) fn copy_from(&mut self, other: &ArgMatches, name: &str) {
+        self.values.remove(name);
+
+        if let Some(values) = other.values.get(name) {
+            self.values.insert(name.to_string(), values.clone());
+        }
+
+        if let Some(source) = other.sources.get(name) {
+            self.sources.insert(name.to_string(), *source);
+        }
+
+        if let Some(count) = other.occurrences.get(name) {
+            self.occurrences.insert(name.to_string(), *count);
+        }
+    }
+
+    // Passes a global value down to nested subcommands that didn't set it themselves.
+    pub(crate) fn propagate(&mut self, name: &str) {
+        let parent = self.clone();
+
+        if let Some((_, child)) = self.subcommand.as_mut() {
+            if child.source(name) != Some(ValueSource::CommandLine) {
+                child.copy_from(&parent, name);
+                child.propagate(name);
+            }
+        }
+    }
+
+    pub fn subcommand(&self) -> Option<(&str, &ArgMatches)> {
+        self.subcommand.as_ref().map(|(name, matches)| (name.as_str(), matches.as_ref()))
+    }
+
+    pub fn subcommand_name(&self) -> Option<&str> {
+        self.subcommand.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    pub fn subcommand_matches(&self, name: &str) -> Option<&ArgMatches> {
+        self.subcommand().filter(|(sub, _)| *sub == name).map(|(_, matches)| matches)
+    }
+
+    pub fn external_subcommand(&self) -> Option<&ExternalSubcommand> {
+        self.external.as_ref()
+    }
+
+    pub(crate) fn push(&mut self, name: &str, value: Option<String>, source: ValueSource) {
+        *self.occurrences.entry(name.to_string()).or_insert(0) += 1;
+        self.sources.insert(name.to_string(), source);
+
+        if let Some(value) = value {
+            self.values.entry(name.to_string()).or_default().push(value);
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.occurrences.contains_key(name)
+    }
+
+    // True when the flag was given, false when absent.
+    pub fn flag(&self, name: &str) -> bool {
+        self.contains(name)
+    }
+
+    pub fn occurrences(&self, name: &str) -> usize {
+        self.occurrences.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn source(&self, name: &str) -> Option<ValueSource> {
+        self.sources.get(name).copied()
+    }
+
+    pub fn value_of(&self, name: &str) -> Option<&str> {
+        self.values.get(name).and_then(|values| values.last()).map(|v| v.as_str())
+    }
+
+    pub fn values_of(&self, name: &str) -> Vec<&str> {
+        self.values
+            .get(name)
+            .map(|values| values.iter().map(|v| v.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn try_get<T: FromStr>(&self, name: &str) -> Result<Option<T>, ArgError>
+    where
+        T::Err: Display,
+    {
+        self.value_of(name).map(|value| parse_value(name, value)).transpose()
+    }
+
+    pub fn try_get_many<T: FromStr>(&self, name: &str) -> Result<Vec<T>, ArgError>
+    where
+        T::Err: Display,
+    {
+        self.values_of(name).into_iter().map(|value| parse_value(name, value)).collect()
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> Option<T>
+    where
+        T::Err: Display,
+    {
+        self.try_get(name).ok().flatten()
+    }
+
+    pub fn get_many<T: FromStr>(&self, name: &str) -> Vec<T>
+    where
+        T::Err: Display,
+    {
+        self.try_get_many(name).unwrap_or_default()
+    }
+}
+
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, ArgError>
+where
+    T::Err: Display,
+{
+    value.parse::<T>().map_err(|e| {
+        ArgError::new(
+            ArgErrorKind::InvalidValue,
+            format!("invalid value {} for {}: {}", quoted(value, ColorChoice::Auto), quoted(name, ColorChoice::Auto), e),
+        )
+    })
+}
@@ -0,0 +1,19 @@
+mod arg;
+mod command;
+mod complete;
+mod error;
+mod help;
+mod man;
+mod matches;
+mod parser;
+mod suggest;
+
+pub use arg::Arg;
+pub use command::Command;
+pub use complete::{Shell, COMPLETE_ARG};
+pub use error::{ArgError, ArgErrorKind};
+pub use help::HelpTheme;
+pub use man::MAN_ARG;
+pub use matches::{ArgMatches, ExternalSubcommand, ValueSource};
+pub use parser::Parser;
+pub use suggest::{edit_distance, suggest};
\ No newline at end of file
@@ -0,0 +1,37 @@
+use super::command::Command;
+use super::error::ArgError;
+use super::matches::ArgMatches;
+
+// Implemented by `#[derive(Parser)]` to build a typed config from the command line.
+pub trait Parser: Sized {
+    fn command() -> Command;
+
+    fn from_matches(matches: &ArgMatches) -> Result<Self, ArgError>;
+
+    fn parse() -> Self {
+        Self::parse_from(std::env::args())
+    }
+
+    fn parse_from<I, T>(args: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        // Goes through `get_matches_from` so completion and `--generate-man` requests are answered
+        let command = Self::command();
+        let matches = command.get_matches_from(args);
+
+        Self::from_matches(&matches).unwrap_or_else(|e| e.with_usage(command.usage()).exit())
+    }
+
+    fn try_parse_from<I, T>(args: I) -> Result<Self, ArgError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let command = Self::command();
+        let matches = command.try_get_matches_from(args)?;
+
+        Self::from_matches(&matches).map_err(|e| e.with_usage(command.usage()))
+    }
+}
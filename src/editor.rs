@@ -0,0 +1,346 @@
+use std::fs;
+use std::path::PathBuf;
+use broccolor::visible_width;
+use crate::errors::Error;
+use crate::input::{read_key, Key};
+use crate::interface::Interface;
+use crate::terminal::enable_raw_mode;
+use crate::widgets::ListConfig;
+
+pub struct Completion {
+    // Char index in the line where the word being completed starts
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+pub trait Completer {
+    fn complete(&self, line: &str, pos: usize) -> Completion;
+}
+
+impl<F: Fn(&str, usize) -> Completion> Completer for F {
+    fn complete(&self, line: &str, pos: usize) -> Completion {
+        self(line, pos)
+    }
+}
+
+pub fn word_start(line: &str, pos: usize) -> usize {
+    let chars: Vec<char> = line.chars().take(pos).collect();
+
+    chars.iter()
+        .rposition(|c| c.is_whitespace())
+        .map(|i| i + 1)
+        .unwrap_or(0)
+}
+
+pub fn common_prefix(candidates: &[String]) -> String {
+    let mut iter = candidates.iter();
+
+    let Some(first) = iter.next() else {
+        return String::new();
+    };
+
+    let mut prefix: Vec<char> = first.chars().collect();
+
+    for candidate in iter {
+        let shared = prefix.iter()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| **a == *b)
+            .count();
+
+        prefix.truncate(shared);
+    }
+
+    prefix.into_iter().collect()
+}
+
+pub struct PathCompleter;
+
+impl Completer for PathCompleter {
+    fn complete(&self, line: &str, pos: usize) -> Completion {
+        let start = word_start(line, pos);
+        let word: String = line.chars().skip(start).take(pos - start).collect();
+
+        let (dir_part, file_prefix) = match word.rfind('/') {
+            Some(i) => (&word[..=i], &word[i + 1..]),
+            None => ("", word.as_str()),
+        };
+
+        let dir = if dir_part.is_empty() {
+            PathBuf::from(".")
+        } else if let (Some(rest), Ok(home)) = (dir_part.strip_prefix("~/"), std::env::var("HOME")) {
+            PathBuf::from(home).join(rest)
+        } else {
+            PathBuf::from(dir_part)
+        };
+
+        let mut candidates = Vec::new();
+
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+
+                if !name.starts_with(file_prefix) || (name.starts_with('.') && !file_prefix.starts_with('.')) {
+                    continue;
+                }
+
+                let is_dir = entry.path().is_dir();
+                candidates.push(format!("{}{}{}", dir_part, name, if is_dir { "/" } else { "" }));
+            }
+        }
+
+        candidates.sort();
+
+        Completion { start, candidates }
+    }
+}
+
+struct Popup {
+    start: usize,
+    candidates: Vec<String>,
+    selected: Option<usize>,
+}
+
+struct EditState {
+    buffer: Vec<char>,
+    cursor: usize,
+    popup: Option<Popup>,
+}
+
+impl EditState {
+    fn line(&self) -> String {
+        self.buffer.iter().collect()
+    }
+
+    // Terminal columns taken by the first `chars` characters, so wide ones count twice.
+    fn width(&self, chars: usize) -> usize {
+        visible_width(&self.buffer[..chars].iter().collect::<String>())
+    }
+
+    fn replace(&mut self, start: usize, text: &str) {
+        self.buffer.splice(start..self.cursor, text.chars());
+        self.cursor = start + text.chars().count();
+    }
+}
+
+pub struct LineEditor {
+    prompt: String,
+    completer: Option<Box<dyn Completer>>,
+    list: ListConfig,
+    interface: Interface,
+}
+
+impl LineEditor {
+    pub fn new(prompt: &str) -> Self {
+        Self {
+            prompt: prompt.to_string(),
+            completer: None,
+            list: ListConfig::default(),
+            interface: Interface::new(),
+        }
+    }
+
+    pub fn completer(mut self, completer: impl Completer + 'static) -> Self {
+        self.completer = Some(Box::new(completer));
+        self
+    }
+
+    pub fn list_style(mut self, list: ListConfig) -> Self {
+        self.list = list;
+        self
+    }
+
+    // Returns `None` when input is cancelled with Ctrl-C or closed with Ctrl-D.
+    pub fn read_line(&mut self) -> Result<Option<String>, Error> {
+        let _raw = enable_raw_mode().map_err(Error::Terminal)?;
+
+        let mut state = EditState {
+            buffer: Vec::new(),
+            cursor: 0,
+            popup: None,
+        };
+
+        self.render(&state)?;
+
+        loop {
+            let key = read_key()?;
+
+            match key {
+                Key::Enter => {
+                    if let Some(Popup { start, candidates, selected: Some(index) }) = state.popup.take() {
+                        state.replace(start, &candidates[index]);
+                    } else {
+                        self.render(&state)?;
+                        self.interface.write("\r\n")?;
+                        return Ok(Some(state.line()));
+                    }
+                }
+                Key::Ctrl('c') => {
+                    state.popup = None;
+                    self.render(&state)?;
+                    self.interface.write("\r\n")?;
+                    return Ok(None);
+                }
+                Key::Ctrl('d') if state.buffer.is_empty() => {
+                    self.interface.write("\r\n")?;
+                    return Ok(None);
+                }
+                Key::Tab => self.complete(&mut state),
+                Key::Down if state.popup.is_some() => self.select(&mut state, 1),
+                Key::Up | Key::BackTab if state.popup.is_some() => self.select(&mut state, -1),
+                Key::Escape => state.popup = None,
+                Key::Char(c) => {
+                    state.buffer.insert(state.cursor, c);
+                    state.cursor += 1;
+                    state.popup = None;
+                }
+                Key::Backspace if state.cursor > 0 => {
+                    state.cursor -= 1;
+                    state.buffer.remove(state.cursor);
+                    state.popup = None;
+                }
+                Key::Delete | Key::Ctrl('d') if state.cursor < state.buffer.len() => {
+                    state.buffer.remove(state.cursor);
+                    state.popup = None;
+                }
+                Key::Left | Key::Ctrl('b') => state.cursor = state.cursor.saturating_sub(1),
+                Key::Right | Key::Ctrl('f') => state.cursor = (state.cursor + 1).min(state.buffer.len()),
+                Key::Home | Key::Ctrl('a') => state.cursor = 0,
+                Key::End | Key::Ctrl('e') => state.cursor = state.buffer.len(),
+                Key::Ctrl('u') => {
+                    state.buffer.drain(..state.cursor);
+                    state.cursor = 0;
+                    state.popup = None;
+                }
+                Key::Ctrl('k') => {
+                    state.buffer.truncate(state.cursor);
+                    state.popup = None;
+                }
+                _ => {}
+            }
+
+            self.render(&state)?;
+        }
+    }
+
+    fn complete(&self, state: &mut EditState) {
+        if state.popup.is_some() {
+            self.select(state, 1);
+            return;
+        }
+
+        let Some(completer) = &self.completer else {
+            return;
+        };
+
+        let Completion { start, candidates } = completer.complete(&state.line(), state.cursor);
+        let start = start.min(state.cursor);
+
+        match candidates.len() {
+            0 => {}
+            1 => {
+                state.replace(start, &candidates[0]);
+
+                if !candidates[0].ends_with('/') {
+                    state.replace(state.cursor, " ");
+                }
+            }
+            _ => {
+                let prefix = common_prefix(&candidates);
+
+                if prefix.chars().count() > state.cursor - start {
+                    state.replace(start, &prefix);
+                }
+
+                state.popup = Some(Popup { start, candidates, selected: None });
+            }
+        }
+    }
+
+    fn select(&self, state: &mut EditState, step: isize) {
+        if let Some(popup) = &mut state.popup {
+            let len = popup.candidates.len() as isize;
+
+            popup.selected = Some(match popup.selected {
+                Some(index) => (index as isize + step).rem_euclid(len) as usize,
+                None if step < 0 => (len - 1) as usize,
+                None => 0,
+            });
+        }
+    }
+
+    fn render(&mut self, state: &EditState) -> Result<(), Error> {
+        let prompt_width = visible_width(&self.prompt);
+        let mut out = format!("\r\x1B[0J{}{}", self.prompt, state.line());
+
+        if let Some(popup) = &state.popup {
+            let height = (self.list.height as usize).max(1);
+            let rows = popup.candidates.len().min(height);
+
+            let offset = match popup.selected {
+                Some(index) if index >= height => index + 1 - height,
+                _ => 0,
+            };
+
+            let indent = " ".repeat(prompt_width + state.width(popup.start));
+
+            for (i, candidate) in popup.candidates.iter().enumerate().skip(offset).take(rows) {
+                out.push_str(&format!("\r\n{}{}", indent, self.list.render_row(candidate, popup.selected == Some(i))));
+            }
+
+            out.push_str(&format!("\x1B[{}A", rows));
+        }
+
+        out.push_str(&format!("\x1B[{}G", prompt_width + state.width(state.cursor) + 1));
+
+        self.interface.write(&out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_start_finds_the_word_before_the_cursor() {
+        assert_eq!(word_start("git che", 7), 4);
+        assert_eq!(word_start("git che", 3), 0);
+        assert_eq!(word_start("git ", 4), 4);
+        assert_eq!(word_start("日本 語", 4), 3);
+    }
+
+    #[test]
+    fn common_prefix_stops_at_the_first_difference() {
+        let candidates = |items: &[&str]| items.iter().map(|item| item.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(common_prefix(&candidates(&["checkout", "cherry-pick", "check"])), "che");
+        assert_eq!(common_prefix(&candidates(&["été", "étage"])), "ét");
+        assert_eq!(common_prefix(&candidates(&["a", "b"])), "");
+        assert_eq!(common_prefix(&[]), "");
+    }
+
+    #[test]
+    fn path_completer_lists_matching_entries() {
+        let dir = std::env::temp_dir().join(format!("broccli-editor-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("setup.sh"), "").unwrap();
+        fs::write(dir.join(".secret"), "").unwrap();
+
+        let line = format!("cat {}/s", dir.display());
+        let completion = PathCompleter.complete(&line, line.chars().count());
+        let hidden = format!("cat {}/.", dir.display());
+        let dotfiles = PathCompleter.complete(&hidden, hidden.chars().count());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(completion.start, 4);
+        assert_eq!(completion.candidates, [format!("{}/setup.sh", dir.display()), format!("{}/src/", dir.display())]);
+        assert_eq!(dotfiles.candidates, [format!("{}/.secret", dir.display())]);
+    }
+
+    #[test]
+    fn cursor_column_counts_wide_characters() {
+        let state = EditState { buffer: "日本a".chars().collect(), cursor: 3, popup: None };
+
+        assert_eq!(state.width(2), 4);
+        assert_eq!(state.width(state.cursor), 5);
+    }
+}
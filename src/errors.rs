@@ -2,6 +2,8 @@ pub enum Error {
     CursorMove,
     Flush(std::io::Error),
     WriteError,
+    Read(std::io::Error),
+    Terminal(std::io::Error),
 }
 
 impl core::fmt::Display for Error {
@@ -9,7 +11,9 @@ impl core::fmt::Display for Error {
         match self {
             Error::CursorMove => write!(f, "Cursor move failed"),
             Error::Flush(e) => write!(f, "Flush failed: {}", e),
-            Error::WriteError => write!(f, "WRite failed!")
+            Error::WriteError => write!(f, "WRite failed!"),
+            Error::Read(e) => write!(f, "Read failed: {}", e),
+            Error::Terminal(e) => write!(f, "Terminal setup failed: {}", e),
         }
     }
 }
@@ -19,7 +23,9 @@ impl core::fmt::Debug for Error {
         match self {
             Error::CursorMove => write!(f, "Cursor move failed"),
             Error::Flush(e) => write!(f, "Flush failed: {}", e),
-            Error::WriteError => write!(f, "WRite failed!")
+            Error::WriteError => write!(f, "WRite failed!"),
+            Error::Read(e) => write!(f, "Read failed: {}", e),
+            Error::Terminal(e) => write!(f, "Terminal setup failed: {}", e),
         }
     }
 }
\ No newline at end of file
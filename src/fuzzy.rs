@@ -0,0 +1,412 @@
+use broccolor::{Color, TextStyle};
+use crate::errors::Error;
+use crate::input::{read_key, Key};
+use crate::interface::Interface;
+use crate::terminal::{enable_raw_mode, get_terminal_size};
+use crate::widgets::{truncate_visible, visible_len, BoxConfig, LineStyle, ListConfig};
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 10;
+const BONUS_CONSECUTIVE: i64 = 8;
+const BONUS_FIRST_CHAR: i64 = 4;
+const PENALTY_GAP: i64 = 1;
+
+fn is_boundary(prev: Option<char>, current: char) -> bool {
+    match prev {
+        None => true,
+        Some(prev) => {
+            matches!(prev, ' ' | '_' | '-' | '/' | '.' | ':' | '\\')
+                || (prev.is_lowercase() && current.is_uppercase())
+                || (!prev.is_ascii_digit() && current.is_ascii_digit())
+        }
+    }
+}
+
+// Scores `candidate` against `query` and returns the matched char positions.
+// The query is matched case-insensitively unless it contains an uppercase char.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let case_sensitive = query.chars().any(|c| c.is_uppercase());
+    let normalize = |c: char| if case_sensitive { c } else { c.to_lowercase().next().unwrap_or(c) };
+
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).map(normalize).collect();
+    let original: Vec<char> = candidate.chars().collect();
+    let text: Vec<char> = original.iter().map(|c| normalize(*c)).collect();
+
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    let (m, n) = (query.len(), text.len());
+
+    if m > n {
+        return None;
+    }
+
+    let bonus: Vec<i64> = (0..n)
+        .map(|j| {
+            let prev = if j == 0 { None } else { Some(original[j - 1]) };
+
+            match (j, is_boundary(prev, original[j])) {
+                (0, _) => BONUS_BOUNDARY + BONUS_FIRST_CHAR,
+                (_, true) => BONUS_BOUNDARY,
+                _ => 0,
+            }
+        })
+        .collect();
+
+    // scores[i][j]: best score with query[i] matched at text[j]
+    let mut scores = vec![vec![None::<i64>; n]; m];
+    let mut parents = vec![vec![0usize; n]; m];
+
+    for j in 0..n {
+        if text[j] == query[0] {
+            // Characters skipped before the first match count as a gap too
+            scores[0][j] = Some(SCORE_MATCH + bonus[j] - j as i64 * PENALTY_GAP);
+        }
+    }
+
+    for i in 1..m {
+        // Best `scores[i - 1][k] + k` seen so far, used to apply the gap penalty lazily
+        let mut best: Option<(i64, usize)> = None;
+
+        for j in i..n {
+            if j >= 2 {
+                if let Some(score) = scores[i - 1][j - 2] {
+                    let value = score + (j - 2) as i64 * PENALTY_GAP;
+
+                    let better = match best {
+                        Some((b, _)) => value > b,
+                        None => true,
+                    };
+
+                    if better {
+                        best = Some((value, j - 2));
+                    }
+                }
+            }
+
+            if text[j] != query[i] {
+                continue;
+            }
+
+            let consecutive = scores[i - 1][j - 1].map(|s| (s + BONUS_CONSECUTIVE, j - 1));
+            let gapped = best.map(|(value, k)| (value - (j - 1) as i64 * PENALTY_GAP, k));
+
+            let chosen = match (consecutive, gapped) {
+                (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                (a, b) => a.or(b),
+            };
+
+            if let Some((score, k)) = chosen {
+                scores[i][j] = Some(score + SCORE_MATCH + bonus[j]);
+                parents[i][j] = k;
+            }
+        }
+    }
+
+    let (mut j, score) = (0..n)
+        .filter_map(|j| scores[m - 1][j].map(|s| (j, s)))
+        .max_by_key(|&(j, s)| (s, std::cmp::Reverse(j)))?;
+
+    let mut positions = vec![0; m];
+
+    for i in (0..m).rev() {
+        positions[i] = j;
+        j = parents[i][j];
+    }
+
+    Some((score, positions))
+}
+
+type Preview<'a> = Box<dyn Fn(&str) -> String + 'a>;
+
+pub struct FuzzyMatch {
+    pub index: usize,
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+
+pub fn fuzzy_filter(query: &str, items: &[String]) -> Vec<FuzzyMatch> {
+    let mut matches: Vec<FuzzyMatch> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            fuzzy_match(query, item).map(|(score, positions)| FuzzyMatch { index, score, positions })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then(a.index.cmp(&b.index)));
+    matches
+}
+
+pub fn highlight_matches(text: &str, positions: &[usize], color: Color) -> String {
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if positions.contains(&i) {
+                c.colorize(color)
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+pub struct FuzzyFinder<'a> {
+    items: Vec<String>,
+    prompt: String,
+    highlight: Color,
+    multi: bool,
+    list: ListConfig,
+    preview: Option<Preview<'a>>,
+    preview_style: BoxConfig,
+}
+
+impl<'a> FuzzyFinder<'a> {
+    pub fn new<T: ToString>(items: &[T]) -> Self {
+        Self {
+            items: items.iter().map(|item| item.to_string()).collect(),
+            prompt: "> ".to_string(),
+            highlight: Color::Green,
+            multi: false,
+            list: ListConfig::default(),
+            preview: None,
+            preview_style: BoxConfig {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+                border_style: LineStyle::Rounded,
+                color: Color::Transparent,
+                background: Color::Transparent,
+            },
+        }
+    }
+
+    pub fn prompt(mut self, prompt: &str) -> Self {
+        self.prompt = prompt.to_string();
+        self
+    }
+
+    pub fn highlight(mut self, color: Color) -> Self {
+        self.highlight = color;
+        self
+    }
+
+    pub fn multi(mut self, multi: bool) -> Self {
+        self.multi = multi;
+        self
+    }
+
+    pub fn list_style(mut self, list: ListConfig) -> Self {
+        self.list = list;
+        self
+    }
+
+    // The position and size of `style` are ignored, the pane fills the right half of the screen.
+    pub fn preview(mut self, preview: impl Fn(&str) -> String + 'a, style: BoxConfig) -> Self {
+        self.preview = Some(Box::new(preview));
+        self.preview_style = style;
+        self
+    }
+
+    // Returns the indices of the chosen items, or `None` when the finder was cancelled.
+    pub fn select(self) -> Result<Option<Vec<usize>>, Error> {
+        let _raw = enable_raw_mode().map_err(Error::Terminal)?;
+        let mut interface = Interface::new();
+
+        interface.enter_alternate_screen()?;
+        let result = self.run(&mut interface);
+        interface.leave_alternate_screen()?;
+
+        result
+    }
+
+    fn run(&self, interface: &mut Interface) -> Result<Option<Vec<usize>>, Error> {
+        let mut query = String::new();
+        let mut matches = fuzzy_filter(&query, &self.items);
+        let mut cursor = 0usize;
+        let mut marked: Vec<usize> = Vec::new();
+
+        loop {
+            self.draw(interface, &query, &matches, cursor, &marked)?;
+
+            let (_, rows) = get_terminal_size().map_err(Error::Terminal)?;
+            let page = (rows as usize).saturating_sub(2).max(1);
+
+            match read_key()? {
+                Key::Enter => {
+                    if !marked.is_empty() {
+                        return Ok(Some(marked));
+                    }
+
+                    // With nothing matching there is nothing to pick, so keep the finder open
+                    if let Some(m) = matches.get(cursor) {
+                        return Ok(Some(vec![m.index]));
+                    }
+                }
+                Key::Escape | Key::Ctrl('c') | Key::Ctrl('g') => return Ok(None),
+                Key::Up | Key::Ctrl('p') | Key::Ctrl('k') => cursor = cursor.saturating_sub(1),
+                Key::Down | Key::Ctrl('n') => {
+                    cursor = (cursor + 1).min(matches.len().saturating_sub(1))
+                }
+                Key::PageUp => cursor = cursor.saturating_sub(page),
+                Key::PageDown => cursor = (cursor + page).min(matches.len().saturating_sub(1)),
+                Key::Tab | Key::BackTab if self.multi => {
+                    if let Some(m) = matches.get(cursor) {
+                        match marked.iter().position(|&i| i == m.index) {
+                            Some(position) => {
+                                marked.remove(position);
+                            }
+                            None => marked.push(m.index),
+                        }
+                    }
+
+                    if cursor + 1 < matches.len() {
+                        cursor += 1;
+                    }
+                }
+                Key::Char(c) => {
+                    query.push(c);
+                    matches = fuzzy_filter(&query, &self.items);
+                    cursor = 0;
+                }
+                Key::Backspace => {
+                    query.pop();
+                    matches = fuzzy_filter(&query, &self.items);
+                    cursor = 0;
+                }
+                Key::Ctrl('u') => {
+                    query.clear();
+                    matches = fuzzy_filter(&query, &self.items);
+                    cursor = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&self, interface: &mut Interface, query: &str, matches: &[FuzzyMatch], cursor: usize, marked: &[usize]) -> Result<(), Error> {
+        let (cols, rows) = get_terminal_size().map_err(Error::Terminal)?;
+        let list_width = if self.preview.is_some() { cols / 2 } else { cols };
+        // At least one row, or the cursor runs past the offset on a tiny terminal
+        let height = rows.saturating_sub(2).max(1);
+
+        interface.hide_cursor()?;
+
+        interface.move_cursor(0, 0)?;
+        interface.clear_line()?;
+        interface.write(&format!("{}{}", self.prompt, query))?;
+
+        let mut status = format!("  {}/{}", matches.len(), self.items.len());
+
+        if self.multi && !marked.is_empty() {
+            status.push_str(&format!(" ({} selected)", marked.len()));
+        }
+
+        interface.move_cursor(0, 1)?;
+        interface.clear_line()?;
+        interface.write(&status.colorize(Color::BrightBlack))?;
+
+        let offset = if cursor >= height as usize { cursor + 1 - height as usize } else { 0 };
+
+        let rendered: Vec<String> = matches
+            .iter()
+            .skip(offset)
+            .take(height as usize)
+            .map(|m| {
+                let text = highlight_matches(&self.items[m.index], &m.positions, self.highlight);
+
+                if self.multi {
+                    let mark = if marked.contains(&m.index) { "● " } else { "  " };
+                    format!("{}{}", mark, text)
+                } else {
+                    text
+                }
+            })
+            .collect();
+
+        let config = ListConfig {
+            x: 0,
+            y: 2,
+            width: list_width,
+            height,
+            ..self.list
+        };
+
+        let selected = if matches.is_empty() { None } else { Some(cursor - offset) };
+        interface.draw_list(config, &rendered, selected)?;
+
+        if let Some(preview) = &self.preview {
+            let pane = BoxConfig {
+                x: list_width,
+                y: 0,
+                width: cols - list_width,
+                height: rows,
+                ..self.preview_style
+            };
+
+            // Redrawn even without a match, which clears the last preview
+            interface.draw_box(pane)?;
+
+            let inner_width = pane.width.saturating_sub(4) as usize;
+            let content = matches.get(cursor).map(|current| preview(&self.items[current.index])).unwrap_or_default();
+
+            for (i, line) in content.lines().take(pane.height.saturating_sub(2) as usize).enumerate() {
+                interface.move_cursor(pane.x as i32 + 2, pane.y as i32 + 1 + i as i32)?;
+                interface.write(&truncate_visible(line, inner_width).background(pane.background))?;
+            }
+        }
+
+        let column = visible_len(&self.prompt) + query.chars().count();
+        interface.move_cursor(column as i32, 0)?;
+        interface.show_cursor()
+    }
+}
+
+// The index of the chosen item, or `None` when the finder was cancelled.
+pub fn fuzzy_select<T: ToString>(items: &[T]) -> Result<Option<usize>, Error> {
+    Ok(FuzzyFinder::new(items).select()?.and_then(|chosen| chosen.first().copied()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn match_positions_prefer_word_boundaries() {
+        assert_eq!(fuzzy_match("fb", "foo_bar").unwrap().1, [0, 4]);
+        assert_eq!(fuzzy_match("gc", "git-checkout").unwrap().1, [0, 4]);
+        assert_eq!(fuzzy_match("xyz", "abc"), None);
+        assert_eq!(fuzzy_match("", "abc"), Some((0, Vec::new())));
+    }
+
+    #[test]
+    fn uppercase_queries_are_case_sensitive() {
+        assert!(fuzzy_match("readme", "README.md").is_some());
+        assert!(fuzzy_match("Readme", "README.md").is_none());
+    }
+
+    #[test]
+    fn leading_gaps_cost_points_in_the_final_score() {
+        let (near, _) = fuzzy_match("b", "xb").unwrap();
+        let (far, _) = fuzzy_match("b", "xxxxb").unwrap();
+
+        assert_eq!(near - far, 3 * PENALTY_GAP);
+    }
+
+    #[test]
+    fn filter_orders_by_score_then_index() {
+        let order = |query: &str, names: &[&str]| {
+            fuzzy_filter(query, &items(names)).into_iter().map(|m| m.index).collect::<Vec<_>>()
+        };
+
+        assert_eq!(order("src", &["resources", "src/main.rs", "x/source"]), [1, 2, 0]);
+        assert_eq!(order("a", &["ba", "ca"]), [0, 1]);
+        assert_eq!(order("q", &["abc"]), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,190 @@
+use libc::{poll, pollfd, read, POLLIN, STDIN_FILENO};
+use crate::errors::Error;
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+// Bytes read ahead of their time, such as keystrokes that arrived while waiting for a terminal reply.
+static PENDING: Mutex<VecDeque<u8>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Ctrl(char),
+    Enter,
+    Tab,
+    BackTab,
+    Backspace,
+    Delete,
+    Escape,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+}
+
+// Makes `bytes` the next ones `read_byte` returns.
+pub(crate) fn unread(bytes: &[u8]) {
+    if let Ok(mut pending) = PENDING.lock() {
+        for byte in bytes.iter().rev() {
+            pending.push_front(*byte);
+        }
+    }
+}
+
+fn take_pending() -> Option<u8> {
+    PENDING.lock().ok()?.pop_front()
+}
+
+pub(crate) fn read_byte() -> Result<u8, Error> {
+    if let Some(byte) = take_pending() {
+        return Ok(byte);
+    }
+
+    let mut byte = 0u8;
+
+    loop {
+        let n = unsafe { read(STDIN_FILENO, &mut byte as *mut u8 as *mut libc::c_void, 1) };
+
+        match n {
+            1 => return Ok(byte),
+            0 => return Err(Error::Read(std::io::ErrorKind::UnexpectedEof.into())),
+            _ => {
+                let error = std::io::Error::last_os_error();
+
+                if error.kind() != std::io::ErrorKind::Interrupted {
+                    return Err(Error::Read(error));
+                }
+            }
+        }
+    }
+}
+
+pub fn poll_input(timeout_ms: i32) -> Result<bool, Error> {
+    if PENDING.lock().is_ok_and(|pending| !pending.is_empty()) {
+        return Ok(true);
+    }
+
+    let mut fds = pollfd { fd: STDIN_FILENO, events: POLLIN, revents: 0 };
+
+    match unsafe { poll(&mut fds, 1, timeout_ms) } {
+        -1 => Err(Error::Read(std::io::Error::last_os_error())),
+        0 => Ok(false),
+        _ => Ok(true),
+    }
+}
+
+// Expects the terminal to be in raw mode, see `enable_raw_mode`.
+pub fn read_key() -> Result<Key, Error> {
+    let byte = read_byte()?;
+
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7f | 0x08 => Key::Backspace,
+        0x1b => read_escape()?,
+        0x01..=0x1a => Key::Ctrl((b'a' + byte - 1) as char),
+        _ => Key::Char(read_utf8(byte)?),
+    };
+
+    Ok(key)
+}
+
+fn read_escape() -> Result<Key, Error> {
+    if !poll_input(25)? {
+        return Ok(Key::Escape);
+    }
+
+    let key = match read_byte()? {
+        b'[' => match read_byte()? {
+            b'A' => Key::Up,
+            b'B' => Key::Down,
+            b'C' => Key::Right,
+            b'D' => Key::Left,
+            b'H' => Key::Home,
+            b'F' => Key::End,
+            b'Z' => Key::BackTab,
+            digit @ b'0'..=b'9' => {
+                let mut code = (digit - b'0') as u32;
+
+                loop {
+                    match read_byte()? {
+                        next @ b'0'..=b'9' => code = code * 10 + (next - b'0') as u32,
+                        b'~' => break,
+                        _ => return Ok(Key::Escape),
+                    }
+                }
+
+                match code {
+                    1 | 7 => Key::Home,
+                    3 => Key::Delete,
+                    4 | 8 => Key::End,
+                    5 => Key::PageUp,
+                    6 => Key::PageDown,
+                    _ => Key::Escape,
+                }
+            }
+            _ => Key::Escape,
+        },
+        b'O' => match read_byte()? {
+            b'H' => Key::Home,
+            b'F' => Key::End,
+            _ => Key::Escape,
+        },
+        _ => Key::Escape,
+    };
+
+    Ok(key)
+}
+
+// A stray continuation byte or an invalid lead byte gives a replacement character on its own,
+// and a sequence cut short leaves the byte that ended it for the next key.
+fn read_utf8(first: u8) -> Result<char, Error> {
+    let len = match first {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => return Ok(char::REPLACEMENT_CHARACTER),
+    };
+
+    let mut bytes = vec![first];
+
+    for _ in 1..len {
+        let byte = read_byte()?;
+
+        if !(0x80..=0xbf).contains(&byte) {
+            unread(&[byte]);
+            return Ok(char::REPLACEMENT_CHARACTER);
+        }
+
+        bytes.push(byte);
+    }
+
+    Ok(String::from_utf8_lossy(&bytes).chars().next().unwrap_or(char::REPLACEMENT_CHARACTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_keys_decode_without_swallowing_the_next_ones() {
+        unread("é".as_bytes());
+        assert_eq!(read_utf8(read_byte().unwrap()).unwrap(), 'é');
+
+        unread(&[0x9f, b'a', b'b', b'c']);
+        assert_eq!(read_utf8(read_byte().unwrap()).unwrap(), char::REPLACEMENT_CHARACTER);
+        assert_eq!(read_byte().unwrap(), b'a');
+        take_pending();
+        take_pending();
+
+        unread(&[0xe6, b'x']);
+        assert_eq!(read_utf8(read_byte().unwrap()).unwrap(), char::REPLACEMENT_CHARACTER);
+        assert_eq!(read_byte().unwrap(), b'x');
+    }
+}
@@ -1,13 +1,19 @@
 use std::io::{stdout, Stdout, Write};
 use broccolor::TextStyle;
 use crate::errors::Error;
-use crate::widgets::{BoxConfig, LineStyle};
+use crate::widgets::{BoxConfig, LineStyle, ListConfig};
 
 pub struct Interface {
     cursor_pos: [i32; 2],
     stdout: Stdout,
 }
 
+impl Default for Interface {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[allow(dead_code)]
 impl Interface {
     pub fn new() -> Self {
@@ -20,7 +26,14 @@ impl Interface {
     pub fn write_escape(&mut self, escape_sequence: &str) -> Result<(), Error> {
         let mut handle = self.stdout.lock();
         write!(handle, "{}", escape_sequence).map_err(|_| Error::CursorMove)?;
-        handle.flush().map_err(|e| Error::Flush(e))?;
+        handle.flush().map_err(Error::Flush)?;
+        Ok(())
+    }
+
+    pub fn write(&mut self, text: &str) -> Result<(), Error> {
+        let mut handle = self.stdout.lock();
+        write!(handle, "{}", text).map_err(|_| Error::WriteError)?;
+        handle.flush().map_err(Error::Flush)?;
         Ok(())
     }
 
@@ -59,6 +72,13 @@ impl Interface {
         Ok(())
     }
 
+    pub fn move_cursor_to_column(&mut self, x: i32) -> Result<(), Error> {
+        let escape = format!("\x1B[{}G", x + 1);
+        self.write_escape(&escape)?;
+        self.cursor_pos[0] = x;
+        Ok(())
+    }
+
     pub fn save_cursor(&mut self) -> Result<(), Error> {
         self.write_escape("\x1B[s")
     }
@@ -67,6 +87,22 @@ impl Interface {
         self.write_escape("\x1B[u")
     }
 
+    pub fn hide_cursor(&mut self) -> Result<(), Error> {
+        self.write_escape("\x1B[?25l")
+    }
+
+    pub fn show_cursor(&mut self) -> Result<(), Error> {
+        self.write_escape("\x1B[?25h")
+    }
+
+    pub fn enter_alternate_screen(&mut self) -> Result<(), Error> {
+        self.write_escape("\x1B[?1049h")
+    }
+
+    pub fn leave_alternate_screen(&mut self) -> Result<(), Error> {
+        self.write_escape("\x1B[?1049l")
+    }
+
     pub fn scroll_up(&mut self, n: i32) -> Result<(), Error> {
         let escape = format!("\x1B[{}S", n);
         self.write_escape(&escape)
@@ -94,6 +130,7 @@ impl Interface {
     pub fn clear_from_cursor_to_begin(&mut self) -> Result<(), Error> {
         self.write_escape("\x1B[1J")
     }
+
     pub fn draw_box(&mut self, config: BoxConfig) -> Result<(), Error> {
         let (x, y, width, height) = (config.x as i32, config.y as i32, config.width as i32, config.height as i32);
         let border = match config.border_style {
@@ -137,6 +174,29 @@ impl Interface {
         self.move_cursor(x, y + height - 1)?;
         write!(self.stdout, "{}{}{}", border_bottom_left, border_bottom, border_bottom_right).map_err(|_| Error::WriteError)?;
 
-        self.stdout.flush().map_err(|e| Error::Flush(e))
+        self.stdout.flush().map_err(Error::Flush)
     }
-}
+
+    pub fn draw_list(&mut self, config: ListConfig, items: &[String], selected: Option<usize>) -> Result<(), Error> {
+        let (x, y, height) = (config.x as i32, config.y as i32, config.height as usize);
+
+        // Keep the selected item in view
+        let offset = match selected {
+            Some(index) if index >= height => index + 1 - height,
+            _ => 0,
+        };
+
+        for row in 0..height {
+            self.move_cursor(x, y + row as i32)?;
+
+            let line = match items.get(offset + row) {
+                Some(item) => config.render_row(item, selected == Some(offset + row)),
+                None => config.render_row("", false),
+            };
+
+            write!(self.stdout, "{}", line).map_err(|_| Error::WriteError)?;
+        }
+
+        self.stdout.flush().map_err(Error::Flush)
+    }
+}
\ No newline at end of file
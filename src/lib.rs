@@ -1,10 +1,20 @@
+pub mod args;
+pub mod editor;
 pub mod errors;
+pub mod fuzzy;
+pub mod input;
 pub mod interface;
+pub mod pager;
 pub mod terminal;
 pub mod widgets;
 pub use brocproc;
-pub use broccolor::{Color, ColoredText, TextStyle};
+pub use broccolor::{ansi_to_html, background_from_env, background_from_vars, best_text_color_for, ensure_contrast, html_stylesheet, parse_ansi, render_ansi, slice, spans_to_html, strip_ansi, truncate, visible_width, Attributes, Color, ColorChoice, ColorConversion, ColorLevel, ColorSpace, ColoredText, Direction, Gradient, Hsl, Hsv, HtmlMode, Lab, Lch, Oklab, Oklch, Role, Stream, Style, StyledText, TextStyle, Theme, VisionDeficiency};
+pub use args::*;
+pub use editor::*;
+pub use fuzzy::*;
+pub use input::*;
 pub use interface::*;
+pub use pager::*;
 pub use terminal::*;
 pub use widgets::*;
 pub use brocproc::*;
\ No newline at end of file
@@ -1,4 +1,4 @@
-use brocproc::{xprintb, xprintln};
+use brocproc::xprintb;
 use broccli::errors::Error;
 use broccolor::Color;
 
@@ -0,0 +1,359 @@
+use std::io::{stdout, Write};
+use broccolor::{slice, visible_width};
+use libc::{STDIN_FILENO, STDOUT_FILENO};
+use crate::errors::Error;
+use crate::input::{read_key, Key};
+use crate::interface::Interface;
+use crate::terminal::{enable_raw_mode, get_terminal_size, is_terminal};
+
+// Shows `text` in the alternate screen when it doesn't fit the terminal, otherwise prints it
+// as is. So does a `stdin` or `stdout` that isn't a terminal, as in `cmd | tool | less`.
+pub fn page(text: &str) -> Result<(), Error> {
+    let size = get_terminal_size()
+        .ok()
+        .filter(|_| is_terminal(STDOUT_FILENO) && is_terminal(STDIN_FILENO));
+
+    match size {
+        Some((cols, rows)) if !fits(text, cols, rows) => match enable_raw_mode() {
+            Ok(_raw) => Pager::new(text).run(cols, rows),
+            Err(_) => print(text),
+        },
+        _ => print(text),
+    }
+}
+
+fn print(text: &str) -> Result<(), Error> {
+    let mut handle = stdout().lock();
+    write!(handle, "{}", text).map_err(|_| Error::WriteError)?;
+
+    if !text.ends_with('\n') {
+        writeln!(handle).map_err(|_| Error::WriteError)?;
+    }
+
+    handle.flush().map_err(Error::Flush)
+}
+
+// Whether `text` fits above the prompt once long lines wrap at `cols`.
+fn fits(text: &str, cols: u16, rows: u16) -> bool {
+    let cols = (cols as usize).max(1);
+    let height: usize = text.lines().map(|line| visible_width(line).div_ceil(cols).max(1)).sum();
+
+    height < rows as usize
+}
+
+struct Pager {
+    lines: Vec<String>,
+    // SGR sequences still active at the start of each line
+    carry: Vec<String>,
+    top: usize,
+    // First visible column, for lines wider than the screen
+    left: usize,
+    widest: usize,
+    query: String,
+    message: Option<String>,
+}
+
+impl Pager {
+    fn new(text: &str) -> Self {
+        let lines: Vec<String> = text.lines().map(|line| line.to_string()).collect();
+        let mut carry = Vec::with_capacity(lines.len());
+        let mut active = String::new();
+
+        for line in &lines {
+            carry.push(active.clone());
+
+            for sequence in sgr_sequences(line) {
+                if sequence == "\x1B[0m" || sequence == "\x1B[m" {
+                    active.clear();
+                } else {
+                    active.push_str(sequence);
+                }
+            }
+        }
+
+        let widest = lines.iter().map(|line| visible_width(line)).max().unwrap_or(0);
+
+        Self {
+            lines,
+            carry,
+            top: 0,
+            left: 0,
+            widest,
+            query: String::new(),
+            message: None,
+        }
+    }
+
+    // Expects the terminal to be in raw mode already.
+    fn run(&mut self, cols: u16, rows: u16) -> Result<(), Error> {
+        let mut interface = Interface::new();
+
+        interface.enter_alternate_screen()?;
+        interface.hide_cursor()?;
+        let result = self.event_loop(&mut interface, cols, rows);
+        interface.show_cursor()?;
+        interface.leave_alternate_screen()?;
+
+        result
+    }
+
+    fn event_loop(&mut self, interface: &mut Interface, mut cols: u16, mut rows: u16) -> Result<(), Error> {
+        loop {
+            if let Ok(size) = get_terminal_size() {
+                (cols, rows) = size;
+            }
+
+            let height = (rows as usize).saturating_sub(1).max(1);
+            let last = self.lines.len().saturating_sub(height);
+
+            let step = (cols as usize / 2).max(1);
+            let rightmost = self.widest.saturating_sub(cols as usize);
+
+            self.top = self.top.min(last);
+            self.left = self.left.min(rightmost);
+            self.draw(interface, cols, height)?;
+            self.message = None;
+
+            match read_key()? {
+                Key::Char('q') | Key::Char('Q') | Key::Escape | Key::Ctrl('c') => return Ok(()),
+                Key::Char('j') | Key::Down | Key::Enter | Key::Ctrl('n') => self.top = (self.top + 1).min(last),
+                Key::Char('k') | Key::Up | Key::Ctrl('p') => self.top = self.top.saturating_sub(1),
+                Key::Char(' ') | Key::Char('f') | Key::PageDown | Key::Ctrl('f') => {
+                    self.top = (self.top + height).min(last)
+                }
+                Key::Char('b') | Key::PageUp | Key::Ctrl('b') => self.top = self.top.saturating_sub(height),
+                Key::Char('d') | Key::Ctrl('d') => self.top = (self.top + height / 2).min(last),
+                Key::Char('u') | Key::Ctrl('u') => self.top = self.top.saturating_sub(height / 2),
+                Key::Char('l') | Key::Right => self.left = (self.left + step).min(rightmost),
+                Key::Char('h') | Key::Left => self.left = self.left.saturating_sub(step),
+                Key::Char('g') | Key::Home => self.top = 0,
+                Key::Char('G') | Key::End => self.top = last,
+                Key::Char('/') => {
+                    if let Some(query) = self.prompt(interface, rows)? {
+                        self.query = query;
+                        self.search(self.top, true);
+                    }
+                }
+                Key::Char('n') => self.search(self.top + 1, true),
+                Key::Char('N') => self.search(self.top.saturating_sub(1), false),
+                _ => {}
+            }
+        }
+    }
+
+    fn search(&mut self, from: usize, forward: bool) {
+        if self.query.is_empty() {
+            return;
+        }
+
+        let query = self.query.to_lowercase();
+        let contains = |line: &String| strip_sgr(line).to_lowercase().contains(&query);
+
+        let found = if forward {
+            self.lines.iter().enumerate().skip(from).find(|(_, line)| contains(line))
+        } else {
+            self.lines.iter().enumerate().take(from + 1).rev().find(|(_, line)| contains(line))
+        };
+
+        match found {
+            Some((index, _)) => self.top = index,
+            None => self.message = Some(format!("Pattern not found: {}", self.query)),
+        }
+    }
+
+    fn prompt(&mut self, interface: &mut Interface, rows: u16) -> Result<Option<String>, Error> {
+        let mut query = String::new();
+
+        interface.show_cursor()?;
+
+        let result = loop {
+            interface.move_cursor(0, rows as i32 - 1)?;
+            interface.clear_line()?;
+            interface.write(&format!("/{}", query))?;
+
+            match read_key()? {
+                Key::Enter => break Some(query),
+                Key::Escape | Key::Ctrl('c') => break None,
+                Key::Backspace if query.is_empty() => break None,
+                Key::Backspace => {
+                    query.pop();
+                }
+                Key::Char(c) => query.push(c),
+                _ => {}
+            }
+        };
+
+        interface.hide_cursor()?;
+
+        Ok(result)
+    }
+
+    fn draw(&self, interface: &mut Interface, cols: u16, height: usize) -> Result<(), Error> {
+        for row in 0..height {
+            interface.move_cursor(0, row as i32)?;
+            interface.clear_line()?;
+
+            if let Some(line) = self.lines.get(self.top + row) {
+                let line = highlight_matches(line, &self.query);
+                let line = slice(&line, self.left, self.left + cols as usize);
+                interface.write(&format!("{}{}\x1B[0m", self.carry[self.top + row], line))?;
+            } else {
+                interface.write("~")?;
+            }
+        }
+
+        let bottom = (self.top + height).min(self.lines.len());
+        let percent = bottom * 100 / self.lines.len().max(1);
+
+        let status = match &self.message {
+            Some(message) => message.clone(),
+            None if self.left > 0 => {
+                format!("lines {}-{}/{} {}% col {}", self.top + 1, bottom, self.lines.len(), percent, self.left + 1)
+            }
+            None => format!("lines {}-{}/{} {}%", self.top + 1, bottom, self.lines.len(), percent),
+        };
+
+        interface.move_cursor(0, height as i32)?;
+        interface.clear_line()?;
+        interface.write(&format!("\x1B[7m {} \x1B[27m", status))
+    }
+}
+
+fn sgr_sequences(line: &str) -> Vec<&str> {
+    let mut sequences = Vec::new();
+    let mut rest = line;
+
+    while let Some(start) = rest.find("\x1B[") {
+        let after = &rest[start + 2..];
+
+        match after.find(|c: char| c.is_ascii_alphabetic()) {
+            Some(end) => {
+                if after.as_bytes()[end] == b'm' {
+                    sequences.push(&rest[start..start + 2 + end + 1]);
+                }
+
+                rest = &after[end + 1..];
+            }
+            None => break,
+        }
+    }
+
+    sequences
+}
+
+fn strip_sgr(line: &str) -> String {
+    let mut out = String::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1B' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+
+    out
+}
+
+// Wraps case-insensitive matches of `query` in reverse video, leaving existing colors intact.
+fn highlight_matches(line: &str, query: &str) -> String {
+    if query.is_empty() {
+        return line.to_string();
+    }
+
+    // Byte offset in `line` of every visible char
+    let mut visible: Vec<(usize, char)> = Vec::new();
+    let mut iter = line.char_indices();
+
+    while let Some((i, c)) = iter.next() {
+        if c == '\x1B' {
+            for (_, c) in iter.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            visible.push((i, c));
+        }
+    }
+
+    let haystack: Vec<char> = visible.iter().map(|(_, c)| c.to_lowercase().next().unwrap_or(*c)).collect();
+    let needle: Vec<char> = query.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect();
+
+    let mut ranges = Vec::new();
+    let mut i = 0;
+
+    while i + needle.len() <= haystack.len() {
+        if haystack[i..i + needle.len()] == needle[..] {
+            let start = visible[i].0;
+            let (last, c) = visible[i + needle.len() - 1];
+            ranges.push((start, last + c.len_utf8()));
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+
+    let mut out = String::new();
+    let mut position = 0;
+
+    for (start, end) in ranges {
+        out.push_str(&line[position..start]);
+        out.push_str("\x1B[7m");
+        out.push_str(&line[start..end]);
+        out.push_str("\x1B[27m");
+        position = end;
+    }
+
+    out.push_str(&line[position..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_only_when_wrapped_lines_overflow() {
+        assert!(fits("a\nb\nc", 80, 4));
+        assert!(!fits("a\nb\nc\nd", 80, 4));
+        assert!(!fits(&"x".repeat(200), 80, 3));
+        assert!(fits(&format!("\x1B[31m{}\x1B[0m", "x".repeat(80)), 80, 2));
+        assert!(!fits("日本語", 4, 2));
+    }
+
+    #[test]
+    fn styles_carry_over_to_the_next_lines() {
+        let pager = Pager::new("\x1B[1mbold\nstill\x1B[0m\nplain");
+
+        assert_eq!(pager.carry, ["", "\x1B[1m", ""]);
+        assert_eq!(pager.widest, 5);
+    }
+
+    #[test]
+    fn search_moves_to_matches_and_reports_misses() {
+        let mut pager = Pager::new("alpha\nBeta\ngamma\nbeta");
+        pager.query = "beta".to_string();
+
+        pager.search(0, true);
+        assert_eq!(pager.top, 1);
+        pager.search(pager.top + 1, true);
+        assert_eq!(pager.top, 3);
+        pager.search(pager.top - 1, false);
+        assert_eq!(pager.top, 1);
+
+        pager.query = "delta".to_string();
+        pager.search(0, true);
+        assert_eq!(pager.message.as_deref(), Some("Pattern not found: delta"));
+    }
+
+    #[test]
+    fn matches_are_highlighted_around_existing_colors() {
+        assert_eq!(highlight_matches("a\x1B[31mbc\x1B[0m", "ab"), "\x1B[7ma\x1B[31mb\x1B[27mc\x1B[0m");
+    }
+}
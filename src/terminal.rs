@@ -1,4 +1,11 @@
-use libc::{ioctl, winsize, STDOUT_FILENO, TIOCGWINSZ};
+use crate::input::{poll_input, read_byte, unread};
+use broccolor::{background_from_env, Color, Theme};
+use libc::{c_int, ioctl, isatty, tcgetattr, tcsetattr, termios, winsize, STDIN_FILENO, STDOUT_FILENO, TCSAFLUSH, TCSANOW, TIOCGWINSZ};
+
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 pub fn get_terminal_size() -> Result<(u16, u16), std::io::Error> {
     let mut size: winsize = unsafe { std::mem::zeroed() };
@@ -9,3 +16,217 @@ pub fn get_terminal_size() -> Result<(u16, u16), std::io::Error> {
 
     Ok((size.ws_col, size.ws_row))
 }
+
+pub fn is_terminal(fd: i32) -> bool {
+    unsafe { isatty(fd) == 1 }
+}
+
+// Restores the original terminal attributes when dropped.
+pub struct RawMode {
+    original: termios,
+    // When the change takes effect: `TCSAFLUSH` drops pending input, `TCSANOW` keeps it
+    action: c_int,
+}
+
+// How many `RawMode` guards are alive.
+static RAW_GUARDS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn enable_raw_mode() -> Result<RawMode, std::io::Error> {
+    raw_mode(TCSAFLUSH)
+}
+
+fn raw_mode(action: c_int) -> Result<RawMode, std::io::Error> {
+    let mut original: termios = unsafe { std::mem::zeroed() };
+
+    if unsafe { tcgetattr(STDIN_FILENO, &mut original) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    let mut raw = original;
+    raw.c_iflag &= !(libc::IXON | libc::ICRNL | libc::BRKINT | libc::INPCK | libc::ISTRIP);
+    raw.c_lflag &= !(libc::ECHO | libc::ICANON | libc::ISIG | libc::IEXTEN);
+    raw.c_cc[libc::VMIN] = 1;
+    raw.c_cc[libc::VTIME] = 0;
+
+    if unsafe { tcsetattr(STDIN_FILENO, action, &raw) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    RAW_GUARDS.fetch_add(1, Ordering::SeqCst);
+
+    Ok(RawMode { original, action })
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe {
+            tcsetattr(STDIN_FILENO, self.action, &self.original);
+        }
+
+        RAW_GUARDS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+const QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+static BACKGROUND: OnceLock<Option<Color>> = OnceLock::new();
+
+// Asks the terminal for one of its dynamic colors (10 is the foreground, 11 the background).
+// A primary device attributes request goes out after it, which every terminal answers,
+// so one that ignores the color query doesn't make us wait out the whole timeout.
+fn query_color(code: u8, timeout: Duration) -> Option<Color> {
+    if !is_terminal(STDIN_FILENO) || !is_terminal(STDOUT_FILENO) || std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return None;
+    }
+
+    // Inside the editor, pager or fuzzy finder the terminal is raw already, and switching modes
+    // under their guard would restore cooked mode too early. Keystrokes typed ahead are kept either way.
+    let _raw = match RAW_GUARDS.load(Ordering::SeqCst) {
+        0 => Some(raw_mode(TCSANOW).ok()?),
+        _ => None,
+    };
+
+    let mut stdout = std::io::stdout();
+
+    write!(stdout, "\x1b]{};?\x1b\\\x1b[c", code).ok()?;
+    stdout.flush().ok()?;
+
+    let deadline = Instant::now() + timeout;
+    let mut reply = Vec::new();
+
+    // Replies arrive in order, so once the device attributes start the color reply is complete
+    while !reply.windows(3).any(|w| w == b"\x1b[?") || reply.last() != Some(&b'c') {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+
+        if remaining.is_zero() || !poll_input(remaining.as_millis() as i32).ok()? {
+            break;
+        }
+
+        reply.push(read_byte().ok()?);
+    }
+
+    // Anything else read meanwhile was typed by the user, so it goes back for the next read
+    let color = take_reply(&mut reply, format!("\x1b]{};", code).as_bytes(), &[b"\x07", b"\x1b\\"]);
+    take_reply(&mut reply, b"\x1b[?", &[b"c"]);
+    unread(&reply);
+
+    parse_color_reply(&String::from_utf8_lossy(&color?))
+}
+
+// Removes the first `prefix ... terminator` run from `bytes` and returns what lies in between.
+// A reply cut short by the timeout runs to the end.
+fn take_reply(bytes: &mut Vec<u8>, prefix: &[u8], terminators: &[&[u8]]) -> Option<Vec<u8>> {
+    let start = bytes.windows(prefix.len()).position(|window| window == prefix)?;
+    let body = start + prefix.len();
+
+    let (end, length) = terminators
+        .iter()
+        .filter_map(|terminator| {
+            bytes[body..]
+                .windows(terminator.len())
+                .position(|window| window == *terminator)
+                .map(|i| (body + i, terminator.len()))
+        })
+        .min()
+        .unwrap_or((bytes.len(), 0));
+
+    let inner = bytes[body..end].to_vec();
+    bytes.drain(start..end + length);
+
+    Some(inner)
+}
+
+// X11 color specs as terminals report them: `rgb:RRRR/GGGG/BBBB`, with 1 to 4 hex digits
+// per channel, `rgba:` with a fourth ignored channel, or `#RGB` through `#RRRRGGGGBBBB`.
+fn parse_color_reply(spec: &str) -> Option<Color> {
+    if let Some(hex) = spec.strip_prefix('#') {
+        if !hex.is_ascii() || hex.is_empty() || hex.len() % 3 != 0 {
+            return None;
+        }
+
+        let digits = hex.len() / 3;
+
+        return Some(Color::Rgb(
+            scale_channel(&hex[..digits])?,
+            scale_channel(&hex[digits..2 * digits])?,
+            scale_channel(&hex[2 * digits..])?,
+        ));
+    }
+
+    let channels = spec.strip_prefix("rgb:").or_else(|| spec.strip_prefix("rgba:"))?;
+    let mut values = channels.split('/').map(scale_channel);
+
+    Some(Color::Rgb(values.next()??, values.next()??, values.next()??))
+}
+
+// Scales 1 to 4 hex digits from their own range, e.g. 0–0xFFFF for four digits, to 0–255.
+fn scale_channel(channel: &str) -> Option<u8> {
+    if !(1..=4).contains(&channel.len()) || !channel.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let value = u32::from_str_radix(channel, 16).ok()?;
+    let max = 16u32.pow(channel.len() as u32) - 1;
+
+    Some(((value * 255 + max / 2) / max) as u8)
+}
+
+// The terminal's default foreground color through OSC 10, or `None` when it doesn't answer in time.
+pub fn query_foreground_color(timeout: Duration) -> Option<Color> {
+    query_color(10, timeout)
+}
+
+// The terminal's default background color through OSC 11, or `None` when it doesn't answer in time.
+pub fn query_background_color(timeout: Duration) -> Option<Color> {
+    query_color(11, timeout)
+}
+
+// The background from OSC 11, falling back to `COLORFGBG`. Queried once and cached.
+pub fn background_color() -> Option<Color> {
+    *BACKGROUND.get_or_init(|| query_background_color(QUERY_TIMEOUT).or_else(background_from_env))
+}
+
+// Assumes a dark background when the terminal can't tell, as most are.
+pub fn is_dark_background() -> bool {
+    background_color().and_then(|background| background.is_dark()).unwrap_or(true)
+}
+
+// The built-in theme that suits the terminal's background.
+pub fn background_theme() -> Theme {
+    Theme::for_background(is_dark_background())
+}
+
+// Makes `background_theme` the current theme, so `Theme::current` follows the background the
+// terminal reports instead of `COLORFGBG` alone.
+pub fn install_background_theme() {
+    Theme::set_current(background_theme());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_replies_scale_every_width() {
+        for spec in ["rgb:ffff/8080/0000", "rgb:f/8/0", "rgba:ff/80/00/ff", "#ff8000", "#f80", "#ffff80800000", "#fff808000"] {
+            let Some(Color::Rgb(r, g, b)) = parse_color_reply(spec) else {
+                panic!("{} did not parse", spec);
+            };
+
+            assert_eq!((r, (0x80..=0x88).contains(&g), b), (255, true, 0), "{}", spec);
+        }
+
+        for spec in ["#ff80", "#", "rgb:ff/80", "rgb:fffff/0/0", "rgb:+f/0/0", "cmyk:0/0/0/0"] {
+            assert_eq!(parse_color_reply(spec), None, "{}", spec);
+        }
+    }
+
+    #[test]
+    fn replies_are_cut_out_of_typed_input() {
+        let mut bytes = b"ab\x1b]11;rgb:0/0/0\x1b\\c\x1b[?62;22cd".to_vec();
+
+        assert_eq!(take_reply(&mut bytes, b"\x1b]11;", &[b"\x07", b"\x1b\\"]), Some(b"rgb:0/0/0".to_vec()));
+        assert!(take_reply(&mut bytes, b"\x1b[?", &[b"c"]).is_some());
+        assert_eq!(bytes, b"abcd");
+    }
+}
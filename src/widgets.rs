@@ -1,4 +1,4 @@
-use broccolor::Color;
+use broccolor::{Color, Style};
 
 #[derive(Debug, Clone, Copy)]
 pub struct BoxConfig {
@@ -27,4 +27,94 @@ pub enum LineStyle {
         bottom_right: char,
         bottom_left: char,
     },
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ListConfig {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    pub color: Color,
+    pub background: Color,
+    pub selected_color: Color,
+    pub selected_background: Color,
+}
+
+impl Default for ListConfig {
+    fn default() -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width: 30,
+            height: 8,
+            color: Color::Transparent,
+            background: Color::Transparent,
+            selected_color: Color::Black,
+            selected_background: Color::Cyan,
+        }
+    }
+}
+
+impl ListConfig {
+    // Pads or truncates `item` to the list width and applies the row colors.
+    // Escape sequences inside `item` are kept and don't count towards the width.
+    pub fn render_row(&self, item: &str, selected: bool) -> String {
+        let (color, background, marker) = if selected {
+            (self.selected_color, self.selected_background, "> ")
+        } else {
+            (self.color, self.background, "  ")
+        };
+
+        let width = (self.width as usize).saturating_sub(marker.len());
+        let text = truncate_visible(item, width);
+        let padding = " ".repeat(width.saturating_sub(visible_len(&text)));
+        let style = Style::new().fg(color).bg(background);
+
+        style.paint(format!("{}{}{}", marker, text, padding))
+    }
+}
+
+pub(crate) fn visible_len(text: &str) -> usize {
+    let mut len = 0;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            len += 1;
+        }
+    }
+
+    len
+}
+
+pub(crate) fn truncate_visible(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut len = 0;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            out.push(c);
+
+            for c in chars.by_ref() {
+                out.push(c);
+
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else if len < width {
+            out.push(c);
+            len += 1;
+        }
+    }
+
+    out
+}
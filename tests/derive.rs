@@ -0,0 +1,71 @@
+use broccli::{ArgErrorKind, Parser};
+
+// Runs the `Parser` derive end to end, since its output refers to `broccli::` paths.
+#[derive(Parser, Debug, PartialEq)]
+#[command(name = "tool")]
+struct Cli {
+    #[arg(short, count)]
+    verbose: u8,
+    #[arg(long)]
+    force: bool,
+    #[arg(short = 'j', long, default = 4)]
+    jobs: u32,
+    #[arg(long)]
+    name: Option<String>,
+    files: Vec<String>,
+}
+
+fn parse(args: &[&str]) -> Result<Cli, broccli::ArgError> {
+    Cli::try_parse_from(std::iter::once("tool").chain(args.iter().copied()))
+}
+
+#[test]
+fn fields_take_their_defaults() {
+    assert_eq!(
+        parse(&[]).unwrap(),
+        Cli { verbose: 0, force: false, jobs: 4, name: None, files: Vec::new() },
+    );
+}
+
+#[test]
+fn flags_counts_options_and_positionals() {
+    let cli = parse(&["-vvv", "--force", "-j", "8", "--name", "x", "a.txt", "b.txt"]).unwrap();
+
+    assert_eq!(
+        cli,
+        Cli {
+            verbose: 3,
+            force: true,
+            jobs: 8,
+            name: Some("x".to_string()),
+            files: vec!["a.txt".to_string(), "b.txt".to_string()],
+        },
+    );
+}
+
+#[test]
+fn typed_values_report_parse_errors() {
+    let error = parse(&["--jobs", "many"]).unwrap_err();
+
+    assert_eq!(error.kind, ArgErrorKind::InvalidValue);
+    assert!(error.message.contains("many"), "{}", error.message);
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "needs")]
+struct Needs {
+    #[arg(long, required)]
+    inputs: Vec<String>,
+    count: u32,
+}
+
+#[test]
+fn required_fields_must_be_given() {
+    let parse = |args: &[&str]| Needs::try_parse_from(std::iter::once("needs").chain(args.iter().copied()));
+
+    assert_eq!(parse(&["1"]).unwrap_err().kind, ArgErrorKind::MissingRequired);
+    assert_eq!(parse(&["--inputs", "a"]).unwrap_err().kind, ArgErrorKind::MissingRequired);
+
+    let needs = parse(&["--inputs", "a", "--inputs", "b", "2"]).unwrap();
+    assert_eq!((needs.inputs, needs.count), (vec!["a".to_string(), "b".to_string()], 2));
+}